@@ -0,0 +1,56 @@
+//! Stamps recognition provenance with the chia-wallet-sdk actually compiled
+//! in: the `chia-sdk-driver` entry of the workspace lockfile gives the repo
+//! and tag (or version). Builds without the lockfile, such as from a
+//! packaged crate, fall back to the pinned release.
+
+use std::path::Path;
+
+const FALLBACK_REPO: &str = "xch-dev/chia-wallet-sdk";
+const FALLBACK_REF: &str = "0.33.0";
+
+fn main() {
+    let lockfile = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lockfile.display());
+
+    let (repo, source_ref) = std::fs::read_to_string(&lockfile)
+        .ok()
+        .and_then(|lock| sdk_source(&lock))
+        .unwrap_or_else(|| (FALLBACK_REPO.to_string(), FALLBACK_REF.to_string()));
+    println!("cargo:rustc-env=CHIA_SDK_SOURCE_REPO={repo}");
+    println!("cargo:rustc-env=CHIA_SDK_SOURCE_REF={source_ref}");
+}
+
+/// Repo and ref of the locked `chia-sdk-driver`: the git tag when it comes
+/// from git, otherwise its version.
+fn sdk_source(lock: &str) -> Option<(String, String)> {
+    let entry = lock
+        .split("[[package]]")
+        .find(|entry| entry.contains("name = \"chia-sdk-driver\""))?;
+    let field = |name: &str| {
+        entry.lines().find_map(|line| {
+            line.trim()
+                .strip_prefix(name)?
+                .trim_start()
+                .strip_prefix('=')?
+                .trim()
+                .strip_prefix('"')?
+                .strip_suffix('"')
+                .map(str::to_string)
+        })
+    };
+    let version = field("version")?;
+    let Some(git) = field("source").and_then(|source| source.strip_prefix("git+").map(str::to_string))
+    else {
+        return Some((FALLBACK_REPO.to_string(), version));
+    };
+    let (url, rest) = git.split_once('?').unwrap_or((git.as_str(), ""));
+    let repo = url
+        .trim_start_matches("https://github.com/")
+        .trim_end_matches(".git")
+        .to_string();
+    let tag = rest
+        .split(['&', '#'])
+        .find_map(|param| param.strip_prefix("tag="))
+        .map_or(version, str::to_string);
+    Some((repo, tag))
+}
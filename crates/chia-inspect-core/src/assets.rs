@@ -0,0 +1,55 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::schema::InspectionOutput;
+
+/// The distinct CAT asset ids the bundle touches, read from the `asset_id`
+/// of every recognized `cat_layer`.
+pub fn collect_asset_ids(output: &InspectionOutput) -> BTreeSet<String> {
+    output
+        .result
+        .spends
+        .iter()
+        .flat_map(|spend| &spend.puzzle.recognition.wrappers)
+        .filter(|wrapper| wrapper.name == "cat_layer")
+        .filter_map(|wrapper| wrapper.params.get("asset_id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chia_bls::PublicKey;
+    use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
+    use chia_sdk_driver::{CatLayer, Layer, SpendContext, StandardLayer};
+    use clvm_utils::tree_hash_from_bytes;
+    use clvmr::serde::node_to_bytes;
+
+    use crate::analyze_spends;
+    use crate::network::Network;
+    use crate::recognize::recognize_puzzle_structure;
+
+    #[test]
+    fn collects_distinct_cat_asset_ids() {
+        let puzzle = Program::from(vec![0x01]);
+        let puzzle_hash = tree_hash_from_bytes(puzzle.as_ref()).expect("puzzle hash");
+        let coin = Coin::new(Bytes32::new([1; 32]), puzzle_hash.into(), 0);
+        let spend = CoinSpend::new(coin, puzzle, Program::from(vec![0x80]));
+        let mut output = analyze_spends(&[spend], Network::Mainnet).expect("analyze");
+        assert!(collect_asset_ids(&output).is_empty());
+
+        let mut ctx = SpendContext::new();
+        let cat = CatLayer::new(Bytes32::new([0xaa; 32]), StandardLayer::new(PublicKey::default()));
+        let cat_puzzle = cat.construct_puzzle(&mut ctx).expect("cat puzzle");
+        output.result.spends[0].puzzle.recognition =
+            recognize_puzzle_structure(&node_to_bytes(&ctx, cat_puzzle).expect("cat puzzle bytes"));
+        let spend = output.result.spends[0].clone();
+        output.result.spends.push(spend);
+        assert_eq!(
+            collect_asset_ids(&output).into_iter().collect::<Vec<_>>(),
+            vec![format!("0x{}", "aa".repeat(32))]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chia_protocol::Bytes32;
+use chia_sha2::Sha256;
+use clvm_utils::tree_hash_from_bytes;
+
+use crate::recognize::{
+    SOLUTION_DEPENDENT_LAYERS, recognize_known_layers, recognize_puzzle_and_solution,
+    recognize_puzzle_structure,
+};
+use crate::schema::PuzzleRecognition;
+
+/// On-disk cache of solution-independent puzzle recognition, keyed by the
+/// sha256 of the puzzle reveal bytes.
+///
+/// Entries live under a per-tool-version subdirectory so a recognizer upgrade
+/// never serves stale layer stacks.
+#[derive(Debug, Clone)]
+pub struct RecognitionCache {
+    dir: PathBuf,
+}
+
+impl RecognitionCache {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().join(env!("CARGO_PKG_VERSION"));
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn get(&self, puzzle_reveal_bytes: &[u8]) -> Option<PuzzleRecognition> {
+        let bytes = std::fs::read(self.entry_path(puzzle_reveal_bytes)).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    pub fn put(&self, puzzle_reveal_bytes: &[u8], recognition: &PuzzleRecognition) -> Result<()> {
+        std::fs::write(
+            self.entry_path(puzzle_reveal_bytes),
+            serde_json::to_vec(recognition)?,
+        )?;
+        Ok(())
+    }
+
+    /// Returns the cached structure, recognizing and storing it on a miss.
+    /// Write failures are ignored; the cache is purely an accelerator.
+    pub fn recognize_structure(&self, puzzle_reveal_bytes: &[u8]) -> PuzzleRecognition {
+        if let Some(recognition) = self.get(puzzle_reveal_bytes) {
+            return recognition;
+        }
+        let recognition = recognize_puzzle_structure(puzzle_reveal_bytes);
+        let _ = self.put(puzzle_reveal_bytes, &recognition);
+        recognition
+    }
+
+    fn entry_path(&self, puzzle_reveal_bytes: &[u8]) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(puzzle_reveal_bytes);
+        self.dir
+            .join(format!("{}.json", hex::encode(hasher.finalize())))
+    }
+}
+
+/// In-memory recognizer for long-running services, such as a mempool
+/// watcher, that inspect many bundles. It remembers the layer stack of each
+/// puzzle hash in an LRU, so a puzzle seen before runs only its own layer
+/// detectors instead of every detector at every depth. The stack is kept
+/// only down to the first layer whose inner puzzle comes from the solution;
+/// below it every detector runs again. Results are the same as
+/// [`recognize_puzzle_and_solution`], parsed solutions included.
+#[derive(Debug)]
+pub struct Recognizer {
+    capacity: usize,
+    tick: u64,
+    layers: HashMap<Bytes32, (Vec<String>, u64)>,
+    /// Last-use tick to puzzle hash; the first entry is evicted first.
+    recency: BTreeMap<u64, Bytes32>,
+}
+
+impl Default for Recognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recognizer {
+    pub const DEFAULT_CAPACITY: usize = 4096;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            layers: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    /// Number of puzzle hashes whose layer stack is remembered.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub fn recognize(&mut self, puzzle_bytes: &[u8], solution_bytes: &[u8]) -> PuzzleRecognition {
+        let Ok(puzzle_hash) = tree_hash_from_bytes(puzzle_bytes).map(Bytes32::from) else {
+            return recognize_puzzle_and_solution(puzzle_bytes, solution_bytes);
+        };
+        if let Some(layers) = self.touch(puzzle_hash) {
+            return recognize_known_layers(puzzle_bytes, solution_bytes, &layers);
+        }
+        let recognition = recognize_puzzle_and_solution(puzzle_bytes, solution_bytes);
+        let solution_dependent = recognition
+            .wrappers
+            .iter()
+            .position(|wrapper| SOLUTION_DEPENDENT_LAYERS.contains(&wrapper.name.as_str()));
+        // An ambiguous depth adds candidates without a wrapper; such puzzles
+        // are not remembered unless the ambiguity lies below a
+        // solution-dependent layer.
+        let known = match solution_dependent {
+            Some(depth) => Some(depth + 1),
+            None => (recognition.candidates.len() == recognition.wrappers.len())
+                .then_some(recognition.wrappers.len()),
+        };
+        if let Some(known) = known {
+            let layers = recognition.wrappers[..known]
+                .iter()
+                .map(|wrapper| wrapper.name.clone())
+                .collect();
+            self.insert(puzzle_hash, layers);
+        }
+        recognition
+    }
+
+    fn touch(&mut self, puzzle_hash: Bytes32) -> Option<Vec<String>> {
+        let (layers, last_used) = self.layers.get_mut(&puzzle_hash)?;
+        self.recency.remove(last_used);
+        self.tick += 1;
+        *last_used = self.tick;
+        self.recency.insert(self.tick, puzzle_hash);
+        Some(layers.clone())
+    }
+
+    fn insert(&mut self, puzzle_hash: Bytes32, layers: Vec<String>) {
+        if self.layers.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.layers.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.recency.insert(self.tick, puzzle_hash);
+        self.layers.insert(puzzle_hash, (layers, self.tick));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_round_trips_structure() {
+        let dir = std::env::temp_dir().join(format!("chia-inspect-cache-{}", std::process::id()));
+        let cache = RecognitionCache::open(&dir).expect("open cache");
+        let puzzle = [0x01_u8];
+
+        assert!(cache.get(&puzzle).is_none());
+        let first = cache.recognize_structure(&puzzle);
+        let cached = cache.get(&puzzle).expect("cached entry");
+        assert_eq!(first.recognized, cached.recognized);
+        assert!(cached.parsed_solution.is_none());
+
+        std::fs::remove_dir_all(&dir).expect("cleanup");
+    }
+
+    #[test]
+    fn recognizer_evicts_least_recently_used() {
+        let mut recognizer = Recognizer::with_capacity(2);
+        let (first, second, third) = ([0x01_u8], [0x80_u8], [0x02_u8]);
+        recognizer.recognize(&first, &[0x80]);
+        recognizer.recognize(&second, &[0x80]);
+        let again = recognizer.recognize(&first, &[0x80]);
+        assert!(!again.recognized);
+
+        recognizer.recognize(&third, &[0x80]);
+        assert_eq!(recognizer.len(), 2);
+        let hash = |bytes: &[u8]| Bytes32::from(tree_hash_from_bytes(bytes).expect("hash"));
+        assert!(recognizer.layers.contains_key(&hash(&first)));
+        assert!(!recognizer.layers.contains_key(&hash(&second)));
+    }
+}
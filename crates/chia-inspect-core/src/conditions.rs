@@ -0,0 +1,337 @@
+use anyhow::{Context, Result, anyhow, bail};
+use chia_sha2::Sha256;
+use chialisp::classic::clvm::OPERATORS_LATEST_VERSION;
+use chialisp::classic::clvm_tools::binutils::disassemble;
+use clvmr::allocator::{Allocator, NodePtr, SExp};
+use clvmr::serde::node_from_bytes_backrefs;
+use clvmr::{ChiaDialect, run_program};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::inspect::DEFAULT_MAX_COST;
+use crate::schema::ConditionInfo;
+use crate::util::encode_hex_prefixed;
+
+pub const AGG_SIG_PARENT: u64 = 43;
+pub const AGG_SIG_UNSAFE: u64 = 49;
+pub const AGG_SIG_ME: u64 = 50;
+pub const CREATE_COIN: u64 = 51;
+pub const RESERVE_FEE: u64 = 52;
+pub const CREATE_COIN_ANNOUNCEMENT: u64 = 60;
+pub const ASSERT_COIN_ANNOUNCEMENT: u64 = 61;
+pub const CREATE_PUZZLE_ANNOUNCEMENT: u64 = 62;
+pub const ASSERT_PUZZLE_ANNOUNCEMENT: u64 = 63;
+pub const RECEIVE_MESSAGE: u64 = 67;
+pub const ASSERT_SECONDS_RELATIVE: u64 = 80;
+pub const ASSERT_SECONDS_ABSOLUTE: u64 = 81;
+pub const ASSERT_HEIGHT_RELATIVE: u64 = 82;
+pub const ASSERT_HEIGHT_ABSOLUTE: u64 = 83;
+pub const ASSERT_BEFORE_SECONDS_RELATIVE: u64 = 84;
+pub const ASSERT_BEFORE_SECONDS_ABSOLUTE: u64 = 85;
+pub const ASSERT_BEFORE_HEIGHT_RELATIVE: u64 = 86;
+pub const ASSERT_BEFORE_HEIGHT_ABSOLUTE: u64 = 87;
+
+/// A condition as emitted by the puzzle, before consensus folds it into
+/// `OwnedSpendConditions`. Only the leading atom arguments are kept; nested
+/// lists such as CREATE_COIN memos end the argument list.
+#[derive(Debug, Clone)]
+pub struct RawCondition {
+    pub opcode: u64,
+    pub args: Vec<Vec<u8>>,
+}
+
+/// Runs a puzzle against its solution and returns the raw condition list.
+///
+/// Consensus evaluation discards announcement payloads and the spend that
+/// produced each AGG_SIG_UNSAFE, so those are recovered from this re-run.
+pub fn run_puzzle_conditions(puzzle_bytes: &[u8], solution_bytes: &[u8]) -> Result<Vec<RawCondition>> {
+    let mut allocator = Allocator::new();
+    let puzzle = node_from_bytes_backrefs(&mut allocator, puzzle_bytes)?;
+    let solution = node_from_bytes_backrefs(&mut allocator, solution_bytes)?;
+    let reduction = run_program(
+        &mut allocator,
+        &ChiaDialect::new(0),
+        puzzle,
+        solution,
+        DEFAULT_MAX_COST,
+    )
+    .map_err(|err| anyhow!("failed to run puzzle: {err:?}"))?;
+    Ok(collect_raw_conditions(&allocator, reduction.1))
+}
+
+pub fn collect_raw_conditions(allocator: &Allocator, list: NodePtr) -> Vec<RawCondition> {
+    let mut conditions = Vec::new();
+    let mut rest = list;
+    while let Some((condition, next)) = allocator.next(rest) {
+        rest = next;
+        let Some((opcode, mut args_ptr)) = allocator.next(condition) else {
+            continue;
+        };
+        let Some(opcode) = atom_bytes(allocator, opcode).and_then(|bytes| atom_to_opcode(&bytes)) else {
+            continue;
+        };
+
+        let mut args = Vec::new();
+        while let Some((arg, next_arg)) = allocator.next(args_ptr) {
+            let Some(bytes) = atom_bytes(allocator, arg) else {
+                break;
+            };
+            args.push(bytes);
+            args_ptr = next_arg;
+        }
+        conditions.push(RawCondition { opcode, args });
+    }
+    conditions
+}
+
+pub fn condition_name(opcode: u64) -> Option<&'static str> {
+    Some(match opcode {
+        1 => "REMARK",
+        43 => "AGG_SIG_PARENT",
+        44 => "AGG_SIG_PUZZLE",
+        45 => "AGG_SIG_AMOUNT",
+        46 => "AGG_SIG_PUZZLE_AMOUNT",
+        47 => "AGG_SIG_PARENT_AMOUNT",
+        48 => "AGG_SIG_PARENT_PUZZLE",
+        49 => "AGG_SIG_UNSAFE",
+        50 => "AGG_SIG_ME",
+        51 => "CREATE_COIN",
+        52 => "RESERVE_FEE",
+        60 => "CREATE_COIN_ANNOUNCEMENT",
+        61 => "ASSERT_COIN_ANNOUNCEMENT",
+        62 => "CREATE_PUZZLE_ANNOUNCEMENT",
+        63 => "ASSERT_PUZZLE_ANNOUNCEMENT",
+        64 => "ASSERT_CONCURRENT_SPEND",
+        65 => "ASSERT_CONCURRENT_PUZZLE",
+        66 => "SEND_MESSAGE",
+        67 => "RECEIVE_MESSAGE",
+        70 => "ASSERT_MY_COIN_ID",
+        71 => "ASSERT_MY_PARENT_ID",
+        72 => "ASSERT_MY_PUZZLEHASH",
+        73 => "ASSERT_MY_AMOUNT",
+        74 => "ASSERT_MY_BIRTH_SECONDS",
+        75 => "ASSERT_MY_BIRTH_HEIGHT",
+        76 => "ASSERT_EPHEMERAL",
+        80 => "ASSERT_SECONDS_RELATIVE",
+        81 => "ASSERT_SECONDS_ABSOLUTE",
+        82 => "ASSERT_HEIGHT_RELATIVE",
+        83 => "ASSERT_HEIGHT_ABSOLUTE",
+        84 => "ASSERT_BEFORE_SECONDS_RELATIVE",
+        85 => "ASSERT_BEFORE_SECONDS_ABSOLUTE",
+        86 => "ASSERT_BEFORE_HEIGHT_RELATIVE",
+        87 => "ASSERT_BEFORE_HEIGHT_ABSOLUTE",
+        90 => "SOFTFORK",
+        _ => return None,
+    })
+}
+
+/// Builds a [`ConditionInfo`] from an unevaluated condition node, for
+/// conditions that live inside a puzzle rather than in consensus output.
+/// Atom args are hex, nested lists become arrays, and `raw` is the disassembly.
+pub fn condition_info_from_node(allocator: &Allocator, condition: NodePtr) -> Option<ConditionInfo> {
+    let (opcode, mut rest) = allocator.next(condition)?;
+    let opcode = atom_bytes(allocator, opcode).and_then(|bytes| atom_to_opcode(&bytes))?;
+    let mut args = Vec::new();
+    while let Some((arg, next)) = allocator.next(rest) {
+        args.push(node_value(allocator, arg));
+        rest = next;
+    }
+    Some(ConditionInfo {
+        opcode: condition_name(opcode)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("UNKNOWN_{opcode}")),
+        args,
+        raw: Some(disassemble(allocator, condition, Some(OPERATORS_LATEST_VERSION))),
+    })
+}
+
+fn node_value(allocator: &Allocator, ptr: NodePtr) -> Value {
+    match allocator.sexp(ptr) {
+        SExp::Atom => json!(encode_hex_prefixed(allocator.atom(ptr).as_ref())),
+        SExp::Pair(..) => {
+            let mut items = Vec::new();
+            let mut rest = ptr;
+            while let Some((item, next)) = allocator.next(rest) {
+                items.push(node_value(allocator, item));
+                rest = next;
+            }
+            Value::Array(items)
+        }
+    }
+}
+
+/// Parses a serialized condition list (the output of running a puzzle) into
+/// structured conditions without re-running anything. Opcodes are named with
+/// the condition table rather than `keyword_from_atom`, whose names are CLVM
+/// operators (`51` would come back as an operator keyword, not `CREATE_COIN`).
+pub fn parse_conditions(bytes: &[u8]) -> Result<Vec<ConditionInfo>> {
+    let mut allocator = Allocator::new();
+    let list = node_from_bytes_backrefs(&mut allocator, bytes).context("failed to decode conditions")?;
+    conditions_from_list(&allocator, list)
+}
+
+/// Runs a delegated puzzle against its solution and parses the conditions
+/// it returns. Standard, p2_delegated_conditions and vault spends all end in
+/// a delegated puzzle, so this shows what such a spend actually asks for,
+/// independent of the layer that carries it.
+pub fn run_delegated(delegated_puzzle_bytes: &[u8], solution_bytes: &[u8]) -> Result<Vec<ConditionInfo>> {
+    let mut allocator = Allocator::new();
+    let puzzle = node_from_bytes_backrefs(&mut allocator, delegated_puzzle_bytes)
+        .context("failed to decode delegated puzzle")?;
+    let solution = node_from_bytes_backrefs(&mut allocator, solution_bytes)
+        .context("failed to decode delegated solution")?;
+    let reduction = run_program(
+        &mut allocator,
+        &ChiaDialect::new(0),
+        puzzle,
+        solution,
+        DEFAULT_MAX_COST,
+    )
+    .map_err(|err| anyhow!("failed to run delegated puzzle: {err:?}"))?;
+    conditions_from_list(&allocator, reduction.1)
+}
+
+fn conditions_from_list(allocator: &Allocator, list: NodePtr) -> Result<Vec<ConditionInfo>> {
+    let mut conditions = Vec::new();
+    let mut rest = list;
+    while let Some((condition, next)) = allocator.next(rest) {
+        let info = condition_info_from_node(allocator, condition)
+            .with_context(|| format!("condition {} is not an (opcode . args) list", conditions.len()))?;
+        conditions.push(info);
+        rest = next;
+    }
+    if !matches!(allocator.sexp(rest), SExp::Atom) || !allocator.atom(rest).as_ref().is_empty() {
+        bail!("conditions list is not nil-terminated");
+    }
+    Ok(conditions)
+}
+
+/// Announcement id as consensus computes it: sha256(origin || message),
+/// where origin is the coin id or the puzzle hash of the announcer.
+pub fn announcement_id(origin: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(origin);
+    hasher.update(message);
+    hasher.finalize()
+}
+
+/// Conditions found on only one side of [`diff_conditions`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConditionDiff {
+    /// Expected but not emitted.
+    pub missing: Vec<ConditionInfo>,
+    /// Emitted but not expected.
+    pub unexpected: Vec<ConditionInfo>,
+}
+
+impl ConditionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Compares conditions by opcode and args, ignoring order and `raw`. Both
+/// sides are multisets: a condition expected twice must be emitted twice.
+pub fn diff_conditions(expected: &[ConditionInfo], actual: &[ConditionInfo]) -> ConditionDiff {
+    let mut unexpected = actual.to_vec();
+    let mut missing = Vec::new();
+    for condition in expected {
+        let found = unexpected.iter().position(|candidate| {
+            candidate.opcode == condition.opcode && candidate.args == condition.args
+        });
+        match found {
+            Some(idx) => {
+                unexpected.remove(idx);
+            }
+            None => missing.push(condition.clone()),
+        }
+    }
+    ConditionDiff {
+        missing,
+        unexpected,
+    }
+}
+
+fn atom_bytes(allocator: &Allocator, ptr: NodePtr) -> Option<Vec<u8>> {
+    match allocator.sexp(ptr) {
+        SExp::Atom => Some(allocator.atom(ptr).as_ref().to_vec()),
+        SExp::Pair(..) => None,
+    }
+}
+
+fn atom_to_opcode(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() > 4 || bytes.first().is_some_and(|b| b & 0x80 != 0) {
+        return None;
+    }
+    Some(bytes.iter().fold(0_u64, |acc, b| (acc << 8) | u64::from(*b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_quoted_condition_list() {
+        // (q . ((60 "hi")))
+        let puzzle = hex::decode("ff01ffff3cff8268698080").expect("puzzle hex");
+        let conditions = run_puzzle_conditions(&puzzle, &[0x80]).expect("run");
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].opcode, CREATE_COIN_ANNOUNCEMENT);
+        assert_eq!(conditions[0].args, vec![b"hi".to_vec()]);
+    }
+
+    #[test]
+    fn parses_serialized_condition_list() {
+        // ((51 0x22.. 1) (60 "hi"))
+        let bytes = hex::decode(format!("ffff33ffa0{}ff0180ffff3cff8268698080", "22".repeat(32)))
+            .expect("hex");
+        let conditions = parse_conditions(&bytes).expect("parse");
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[0].opcode, "CREATE_COIN");
+        assert_eq!(conditions[0].args[1], json!("0x01"));
+        assert_eq!(conditions[1].opcode, "CREATE_COIN_ANNOUNCEMENT");
+        assert!(parse_conditions(&[0x01]).is_err());
+    }
+
+    #[test]
+    fn runs_delegated_puzzle_to_conditions() {
+        // (c (c (q . 60) (c 2 ())) ()) run against ("hi") announces "hi"
+        let puzzle = hex::decode("ff04ffff04ffff013cffff04ff02ff808080ff8080").expect("puzzle hex");
+        let solution = hex::decode("ff82686980").expect("solution hex");
+        let conditions = run_delegated(&puzzle, &solution).expect("run");
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].opcode, "CREATE_COIN_ANNOUNCEMENT");
+        assert_eq!(conditions[0].args, vec![json!("0x6869")]);
+        assert!(run_delegated(&[0x08], &[0x80]).is_err());
+    }
+
+    #[test]
+    fn condition_diff_ignores_order_but_counts_repeats() {
+        let condition = |opcode: &str, arg: Value| ConditionInfo {
+            opcode: opcode.to_string(),
+            args: vec![arg],
+            raw: None,
+        };
+        let actual = [condition("RESERVE_FEE", json!(1)), condition("CREATE_COIN", json!("0x22"))];
+        let expected = [condition("CREATE_COIN", json!("0x22")), condition("RESERVE_FEE", json!(1))];
+        assert!(diff_conditions(&expected, &actual).is_empty());
+
+        let expected = [condition("RESERVE_FEE", json!(1)), condition("RESERVE_FEE", json!(1))];
+        let diff = diff_conditions(&expected, &actual);
+        assert_eq!(diff.missing.len(), 1);
+        assert_eq!(diff.unexpected.len(), 1);
+        assert_eq!(diff.unexpected[0].opcode, "CREATE_COIN");
+    }
+
+    #[test]
+    fn condition_info_from_unevaluated_node() {
+        let mut allocator = Allocator::new();
+        // (61 0xabcd)
+        let node = node_from_bytes_backrefs(&mut allocator, &hex::decode("ff3dff82abcd80").expect("hex"))
+            .expect("node");
+        let info = condition_info_from_node(&allocator, node).expect("condition");
+        assert_eq!(info.opcode, "ASSERT_COIN_ANNOUNCEMENT");
+        assert_eq!(info.args, vec![json!("0xabcd")]);
+        assert!(info.raw.is_some());
+    }
+}
@@ -0,0 +1,36 @@
+use std::collections::BTreeSet;
+
+use crate::schema::{RecognitionCoverage, SpendAnalysis};
+
+/// Tallies how far recognition got on each spend. A spend is fully
+/// recognized when its last layer has no inner puzzle left to recognize,
+/// partially recognized when recognition stopped at an unknown inner
+/// puzzle, and unrecognized when no layer matched at all.
+pub fn recognition_coverage(spends: &[SpendAnalysis]) -> RecognitionCoverage {
+    let mut coverage = RecognitionCoverage {
+        spends: spends.len(),
+        fully_recognized: 0,
+        partially_recognized: 0,
+        unrecognized: 0,
+        unrecognized_puzzle_hashes: BTreeSet::new(),
+        unknown_inner_puzzle_hashes: BTreeSet::new(),
+    };
+    for spend in spends {
+        match spend.puzzle.recognition.wrappers.last() {
+            None => {
+                coverage.unrecognized += 1;
+                coverage
+                    .unrecognized_puzzle_hashes
+                    .insert(spend.coin_spend.coin.puzzle_hash.clone());
+            }
+            Some(wrapper) => match &wrapper.inner_puzzle_tree_hash {
+                None => coverage.fully_recognized += 1,
+                Some(inner) => {
+                    coverage.partially_recognized += 1;
+                    coverage.unknown_inner_puzzle_hashes.insert(inner.clone());
+                }
+            },
+        }
+    }
+    coverage
+}
@@ -0,0 +1,103 @@
+use serde_json::Value;
+
+/// Human-facing string fields. Everything else (byte fields, ids, hashes)
+/// is canonical and never abbreviated.
+const DISPLAY_FIELDS: &[&str] = &[
+    "puzzle_reveal_disasm",
+    "solution_disasm",
+    "puzzle_opd",
+    "solution_opd",
+    "hidden_puzzle_disasm",
+    "disasm",
+    "raw",
+];
+
+/// Abbreviates a `0x` hex string longer than `max_bytes` to
+/// `0xabcd…1234 (512 bytes)`; shorter strings are returned unchanged.
+pub fn truncate_hex(hex: &str, max_bytes: usize) -> String {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    let len = digits.len() / 2;
+    if len <= max_bytes || digits.len() < 8 {
+        return hex.to_string();
+    }
+    format!(
+        "0x{}…{} ({len} bytes)",
+        &digits[..4],
+        &digits[digits.len() - 4..]
+    )
+}
+
+/// Cuts disassembly longer than `max_chars` characters down to its first
+/// `max_chars`, followed by `… (truncated, N chars total)`.
+pub fn truncate_disasm(disasm: String, max_chars: usize) -> String {
+    let total = disasm.chars().count();
+    if total <= max_chars {
+        return disasm;
+    }
+    let kept: String = disasm.chars().take(max_chars).collect();
+    format!("{kept}… (truncated, {total} chars total)")
+}
+
+/// Abbreviates long hex atoms inside the display fields of an output
+/// document, leaving the canonical byte fields at full fidelity.
+pub fn abbreviate_display_hex(value: &mut Value, max_bytes: usize) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                match field {
+                    Value::String(text) if DISPLAY_FIELDS.contains(&key.as_str()) => {
+                        *text = abbreviate_hex_runs(text, max_bytes);
+                    }
+                    _ => abbreviate_display_hex(field, max_bytes),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                abbreviate_display_hex(item, max_bytes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn abbreviate_hex_runs(text: &str, max_bytes: usize) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("0x") {
+        out.push_str(&rest[..start]);
+        let run = &rest[start + 2..];
+        let end = run
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(run.len());
+        out.push_str(&truncate_hex(&rest[start..start + 2 + end], max_bytes));
+        rest = &run[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn abbreviates_only_display_fields() {
+        let long = format!("0x{}", "ab".repeat(64));
+        let mut doc = json!({
+            "puzzle_reveal_bytes": long,
+            "puzzle_opd": format!("(q . {long})"),
+        });
+        abbreviate_display_hex(&mut doc, 32);
+        assert_eq!(doc["puzzle_reveal_bytes"], json!(long));
+        assert_eq!(doc["puzzle_opd"], json!("(q . 0xabab…abab (64 bytes))"));
+        assert_eq!(truncate_hex("0x0102", 32), "0x0102");
+    }
+
+    #[test]
+    fn truncates_long_disasm_with_marker() {
+        assert_eq!(truncate_disasm("(q . 1)".to_string(), 7), "(q . 1)");
+        assert_eq!(truncate_disasm("(q . 1)".to_string(), 4), "(q .… (truncated, 7 chars total)");
+    }
+}
@@ -4,4 +4,14 @@ use thiserror::Error;
 pub enum InspectError {
     #[error("invalid input shape: {0}")]
     InvalidInput(String),
+    #[error("output does not match the inspection schema: {0}")]
+    InvalidOutput(String),
+    #[error("unsupported schema version: {0}")]
+    UnsupportedSchemaVersion(String),
+    #[error("failed to decode puzzle_reveal bytes: {0}")]
+    PuzzleDecode(String),
+    /// Consensus rejected the bundle and the caller asked to stop on that
+    /// (`InspectConfig::fail_fast`) instead of getting a failed document.
+    #[error("bundle failed validation: {0}")]
+    ValidationFailed(String),
 }
@@ -0,0 +1,54 @@
+use serde_json::{Map, Value, json};
+
+use crate::schema::InspectionOutput;
+
+/// Flattens an inspection into one event per fact for log pipelines.
+///
+/// Every event carries the envelope `{bundle_id, spend_index, type}`; the
+/// fact's own fields are merged in alongside it. Bundle-level facts have a
+/// `null` spend_index.
+pub fn output_events(output: &InspectionOutput) -> Vec<Value> {
+    let bundle_id = output.result.bundle_id.as_str();
+    let mut events = Vec::new();
+
+    if let Some(error) = &output.result.error {
+        events.push(event(bundle_id, None, "error", json!(error)));
+    }
+
+    for (idx, spend) in output.result.spends.iter().enumerate() {
+        for condition in &spend.evaluation.conditions {
+            events.push(event(bundle_id, Some(idx), "condition", json!(condition)));
+        }
+        for announcement in &spend.evaluation.announcements {
+            events.push(event(bundle_id, Some(idx), "announcement", announcement.clone()));
+        }
+        for assertion in &spend.evaluation.assertions {
+            events.push(event(bundle_id, Some(idx), "assertion", assertion.clone()));
+        }
+        for flow in &spend.puzzle_behavior.explanation.value_flow {
+            events.push(event(bundle_id, Some(idx), "value_flow", flow.clone()));
+        }
+    }
+
+    for warning in &output.result.warnings {
+        events.push(event(bundle_id, warning.spend_index, "warning", json!(warning)));
+    }
+    for blocker in &output.result.readiness.blockers {
+        events.push(event(bundle_id, None, "readiness_blocker", json!(blocker)));
+    }
+    events
+}
+
+fn event(bundle_id: &str, spend_index: Option<usize>, kind: &str, fact: Value) -> Value {
+    let mut envelope = Map::new();
+    if let Value::Object(fields) = fact {
+        envelope.extend(fields);
+    } else {
+        envelope.insert("value".to_string(), fact);
+    }
+    // Envelope keys win over same-named fact fields.
+    envelope.insert("bundle_id".to_string(), json!(bundle_id));
+    envelope.insert("spend_index".to_string(), json!(spend_index));
+    envelope.insert("type".to_string(), json!(kind));
+    Value::Object(envelope)
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::schema::{CoinRef, InspectionOutput};
+
+/// Renders the bundle's coin graph as Graphviz DOT.
+///
+/// Coins are nodes (removals filled, ephemeral coins double-bordered), spends
+/// are solid edges from the spent coin to each coin it creates, and
+/// announcement dependencies are dashed edges from the announcing coin to the
+/// asserting coin.
+pub fn render_dot(output: &InspectionOutput) -> String {
+    let summary = &output.result.summary;
+    let mut coins = BTreeMap::<&str, (&CoinRef, bool, bool)>::new();
+    for coin in &summary.removals {
+        coins.entry(coin.coin_id.as_str()).or_insert((coin, false, false)).1 = true;
+    }
+    for spend in &output.result.spends {
+        for coin in &spend.evaluation.additions {
+            coins.entry(coin.coin_id.as_str()).or_insert((coin, false, false)).2 = true;
+        }
+    }
+
+    let mut dot = String::from("digraph spend_bundle {\n  rankdir=LR;\n  node [shape=box, fontname=monospace];\n");
+    for (coin_id, (coin, removed, created)) in &coins {
+        let style = match (removed, created) {
+            (true, true) => ", peripheries=2",
+            (true, false) => ", style=filled, fillcolor=lightgrey",
+            _ => "",
+        };
+        let _ = writeln!(
+            dot,
+            "  \"{coin_id}\" [label=\"{}\\n{} mojos\"{style}];",
+            short_id(coin_id),
+            coin.amount
+        );
+    }
+
+    let mut announcers = BTreeMap::<&str, &str>::new();
+    for spend in &output.result.spends {
+        let coin_id = spend.coin_spend.coin.coin_id.as_str();
+        for addition in &spend.evaluation.additions {
+            let _ = writeln!(dot, "  \"{coin_id}\" -> \"{}\";", addition.coin_id);
+        }
+        for announcement in &spend.evaluation.announcements {
+            if let Some(id) = announcement.get("announcement_id").and_then(Value::as_str) {
+                announcers.insert(id, coin_id);
+            }
+        }
+    }
+
+    for spend in &output.result.spends {
+        let coin_id = spend.coin_spend.coin.coin_id.as_str();
+        for assertion in &spend.evaluation.assertions {
+            let Some(id) = assertion.get("announcement_id").and_then(Value::as_str) else {
+                continue;
+            };
+            if assertion.get("self_assertion") == Some(&Value::Bool(true)) {
+                continue;
+            }
+            let Some(announcer) = announcers.get(id) else {
+                continue;
+            };
+            let kind = assertion.get("kind").and_then(Value::as_str).unwrap_or("announcement");
+            let _ = writeln!(
+                dot,
+                "  \"{announcer}\" -> \"{coin_id}\" [style=dashed, label=\"{kind} ann\"];"
+            );
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn short_id(coin_id: &str) -> String {
+    let hex = coin_id.trim_start_matches("0x");
+    if hex.len() <= 12 {
+        return coin_id.to_string();
+    }
+    format!("0x{}…{}", &hex[..6], &hex[hex.len() - 4..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
+    use clvm_traits::ToClvm;
+    use clvm_utils::tree_hash_from_bytes;
+    use clvmr::Allocator;
+    use clvmr::serde::node_to_bytes;
+
+    use crate::analyze_spends;
+    use crate::conditions::{
+        ASSERT_COIN_ANNOUNCEMENT, CREATE_COIN, CREATE_COIN_ANNOUNCEMENT, announcement_id,
+    };
+    use crate::network::Network;
+    use crate::util::encode_hex_prefixed;
+
+    fn identity_spend(parent: u8, conditions: impl ToClvm<Allocator>) -> CoinSpend {
+        let mut allocator = Allocator::new();
+        let solution = conditions.to_clvm(&mut allocator).expect("conditions");
+        let puzzle = Program::from(vec![0x01]);
+        let puzzle_hash = tree_hash_from_bytes(puzzle.as_ref()).expect("puzzle hash");
+        CoinSpend::new(
+            Coin::new(Bytes32::new([parent; 32]), puzzle_hash.into(), 1),
+            puzzle,
+            Program::from(node_to_bytes(&allocator, solution).expect("solution")),
+        )
+    }
+
+    #[test]
+    fn renders_spend_and_announcement_edges() {
+        let message = Bytes32::new([0xaa; 32]);
+        let created = Bytes32::new([0x22; 32]);
+        let announcer = identity_spend(
+            1,
+            ((CREATE_COIN_ANNOUNCEMENT, (message, ())), ((CREATE_COIN, (created, (1u64, ()))), ())),
+        );
+        let id = announcement_id(announcer.coin.coin_id().as_ref(), message.as_ref());
+        let asserter = identity_spend(2, vec![(ASSERT_COIN_ANNOUNCEMENT, (Bytes32::new(id), ()))]);
+        let announcer_id = encode_hex_prefixed(announcer.coin.coin_id().as_ref());
+        let asserter_id = encode_hex_prefixed(asserter.coin.coin_id().as_ref());
+        let child = Coin::new(announcer.coin.coin_id(), created, 1);
+        let child_id = encode_hex_prefixed(child.coin_id().as_ref());
+
+        let output = analyze_spends(&[announcer, asserter], Network::Mainnet).expect("analyze");
+        let dot = render_dot(&output);
+        assert!(dot.starts_with("digraph spend_bundle {"));
+        assert!(dot.contains(&format!("\"{announcer_id}\" [label=")));
+        assert!(dot.contains(&format!("\"{announcer_id}\" -> \"{child_id}\";")));
+        assert!(dot.contains(&format!(
+            "\"{announcer_id}\" -> \"{asserter_id}\" [style=dashed, label=\"coin ann\"];"
+        )));
+    }
+}
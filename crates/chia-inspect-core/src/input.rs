@@ -1,33 +1,110 @@
-use anyhow::{Context, Result, bail};
-use chia_protocol::{CoinSpend, SpendBundle};
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chia_consensus::consensus_constants::TEST_CONSTANTS;
+use chia_consensus::run_block_generator::get_coinspends_for_trusted_block;
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program, SpendBundle};
+use chia_sdk_driver::decode_offer;
 use chia_traits::Streamable;
+use clvm_utils::tree_hash_from_bytes;
+use serde::Deserialize;
 use serde_json::{Map, Value, json};
 
-use crate::util::{decode_hex, normalize_hex_no_prefix};
+use crate::util::{decode_hex, encode_hex_prefixed, normalize_hex_no_prefix};
 
 #[derive(Debug, Clone)]
 pub enum InputSource {
-    Mempool,
-    Block,
+    /// Carries the item's own `additions`/`removals` when the RPC sent them.
+    Mempool(Option<ReportedCoins>),
+    /// Carries the export's own `additions` for the `removals` shape.
+    Block(Option<ReportedCoins>),
     Coin,
+    Generator,
+    /// A bech32m `offer1…` string, a partial spend bundle.
+    Offer,
 }
 
 impl InputSource {
     pub fn kind(&self) -> &'static str {
         match self {
-            Self::Mempool => "mempool_item",
-            Self::Block => "block",
+            Self::Mempool(_) => "mempool_item",
+            Self::Block(_) => "block",
             Self::Coin => "coin",
+            Self::Generator => "block_generator",
+            Self::Offer => "offer",
         }
     }
 }
 
-pub fn load_mempool_blob_input(blob_json: &str) -> Result<(InputSource, SpendBundle, Vec<String>)> {
+/// The coin sets a mempool RPC item reports alongside its spend bundle,
+/// kept as an oracle for the tool's own additions and removals.
+#[derive(Debug, Clone, Default)]
+pub struct ReportedCoins {
+    pub additions: Option<Vec<Coin>>,
+    pub removals: Option<Vec<Coin>>,
+}
+
+/// A parsed input: where it came from, the bundle it normalizes to, and
+/// notes about any reshaping the loader did.
+pub type LoadedInput = (InputSource, SpendBundle, Vec<String>);
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct CoinConfirmation {
+    pub confirmed_height: Option<u32>,
+    pub confirmed_timestamp: Option<u64>,
+}
+
+/// Parses `{ "<coin_id>": { "confirmed_height": .., "confirmed_timestamp": .. } }`.
+pub fn load_spent_context(context_json: &str) -> Result<BTreeMap<Bytes32, CoinConfirmation>> {
+    let raw: BTreeMap<String, CoinConfirmation> =
+        serde_json::from_str(context_json).context("spent context must map coin ids to confirmations")?;
+    let mut out = BTreeMap::new();
+    for (coin_id, confirmation) in raw {
+        let bytes = decode_hex(&coin_id)?;
+        let coin_id = Bytes32::try_from(bytes.as_slice())
+            .with_context(|| format!("spent context key is not a 32-byte coin id: {coin_id}"))?;
+        out.insert(coin_id, confirmation);
+    }
+    Ok(out)
+}
+
+pub fn load_mempool_blob_input(blob_json: &str) -> Result<LoadedInput> {
     let value: Value = serde_json::from_str(blob_json)?;
+    mempool_blob_from_value(&value)
+}
+
+/// One entry of a mempool dump: the bundle name it was keyed by and either
+/// the parsed bundle or why that item could not be parsed.
+pub type MempoolDumpItem = (String, Result<LoadedInput>);
+
+/// Parses the `{bundle_name: mempool_item}` map returned by
+/// `get_all_mempool_items`, either bare or under `mempool_items`. Items are
+/// parsed independently so one bad item does not fail the batch.
+pub fn load_mempool_dump_input(dump_json: &str) -> Result<Vec<MempoolDumpItem>> {
+    let value: Value = serde_json::from_str(dump_json)?;
+    let items = value
+        .get("mempool_items")
+        .unwrap_or(&value)
+        .as_object()
+        .context("mempool dump must be an object mapping bundle names to mempool items")?;
+    Ok(items
+        .iter()
+        .map(|(name, item)| (name.clone(), mempool_blob_from_value(item)))
+        .collect())
+}
+
+fn mempool_blob_from_value(value: &Value) -> Result<LoadedInput> {
     let mut notes = Vec::new();
     let scope = if let Some(wrapper) = value.get("mempool_item") {
         notes.push("input contained mempool_item wrapper; using nested payload".to_string());
         wrapper
+    } else if let Some((key, wrapper)) = ["transaction", "tx"]
+        .into_iter()
+        .find_map(|key| value.get(key).filter(|tx| tx.is_object()).map(|tx| (key, tx)))
+    {
+        notes.push(format!("input contained {key} wrapper; using nested payload"));
+        notes.extend(transaction_metadata_notes(wrapper));
+        wrapper
     } else {
         &value
     };
@@ -41,36 +118,75 @@ pub fn load_mempool_blob_input(blob_json: &str) -> Result<(InputSource, SpendBun
         parse_spend_bundle_object(scope)?
     } else {
         bail!(
-            "mempool blob must contain spend_bundle, spend_bundle_bytes, or coin_spends (top-level or inside mempool_item)"
+            "mempool blob must contain spend_bundle, spend_bundle_bytes, or coin_spends (top-level or inside mempool_item/transaction/tx)"
         );
     };
 
-    Ok((InputSource::Mempool, bundle, notes))
+    let reported = ReportedCoins {
+        additions: reported_coin_list(scope, "additions")?,
+        removals: reported_coin_list(scope, "removals")?,
+    };
+    let reported = (reported.additions.is_some() || reported.removals.is_some()).then_some(reported);
+    Ok((InputSource::Mempool(reported), bundle, notes))
+}
+
+/// The wallet's own view of a transaction record (fee and name), kept as
+/// notes since the inspection recomputes both from the bundle.
+fn transaction_metadata_notes(transaction: &Value) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(fee) = ["fee", "fee_amount"]
+        .into_iter()
+        .find_map(|key| transaction.get(key).and_then(Value::as_u64))
+    {
+        notes.push(format!("transaction reports fee {fee} mojos"));
+    }
+    if let Some(name) = transaction.get("name").and_then(Value::as_str) {
+        notes.push(format!("transaction reports name {name}"));
+    }
+    notes
+}
+
+fn reported_coin_list(scope: &Value, key: &str) -> Result<Option<Vec<Coin>>> {
+    scope
+        .get(key)
+        .map(|coins| {
+            serde_json::from_value(coins.clone())
+                .with_context(|| format!("{key} must be a list of coins"))
+        })
+        .transpose()
 }
 
-pub fn load_block_spends_input(spends_json: &str) -> Result<(InputSource, SpendBundle, Vec<String>)> {
+pub fn load_block_spends_input(spends_json: &str) -> Result<LoadedInput> {
     let value: Value = serde_json::from_str(spends_json)?;
     let mut notes = Vec::new();
 
+    let mut reported = None;
     let spends = if let Some(items) = value.get("coin_spends") {
         parse_coin_spend_list(items)?
     } else if let Some(items) = value.get("block_spends") {
         parse_coin_spend_list(items)?
+    } else if let Some(items) = value.get("removals") {
+        notes.push("block input used the {additions, removals} shape; removals are the coin spends".to_string());
+        reported = reported_coin_list(&value, "additions")?.map(|additions| ReportedCoins {
+            additions: Some(additions),
+            removals: None,
+        });
+        parse_coin_spend_list(items)?
     } else if value.is_array() {
         parse_coin_spend_list(&value)?
     } else {
-        bail!("block input must be an array or object containing coin_spends/block_spends");
+        bail!("block input must be an array or object containing coin_spends/block_spends/removals");
     };
 
     notes.push("block input normalized to SpendBundle with default aggregate signature".to_string());
     Ok((
-        InputSource::Block,
+        InputSource::Block(reported),
         SpendBundle::new(spends, Default::default()),
         notes,
     ))
 }
 
-pub fn load_coin_spend_input(coin_spend_json: &str) -> Result<(InputSource, SpendBundle, Vec<String>)> {
+pub fn load_coin_spend_input(coin_spend_json: &str) -> Result<LoadedInput> {
     let value: Value = serde_json::from_str(coin_spend_json)?;
     let mut notes = Vec::new();
     let spend_value = value.get("coin_spend").unwrap_or(&value);
@@ -83,6 +199,147 @@ pub fn load_coin_spend_input(coin_spend_json: &str) -> Result<(InputSource, Spen
     ))
 }
 
+/// Builds a single coin spend from coin metadata JSON (`{"coin": {...}}` or a
+/// bare coin) plus separately saved puzzle reveal and solution hex.
+pub fn load_coin_parts_input(
+    coin_json: &str,
+    puzzle_hex: &str,
+    solution_hex: &str,
+) -> Result<LoadedInput> {
+    let value: Value = serde_json::from_str(coin_json)?;
+    let coin: Coin = serde_json::from_value(normalize_coin_value(value.get("coin").unwrap_or(&value))?)
+        .context("failed to parse coin JSON")?;
+    let puzzle = Program::from(decode_hex(puzzle_hex.trim()).context("invalid puzzle reveal hex")?);
+    let solution = Program::from(decode_hex(solution_hex.trim()).context("invalid solution hex")?);
+
+    let mut notes = vec!["coin input assembled from separate coin, puzzle and solution files".to_string()];
+    if tree_hash_from_bytes(puzzle.as_ref()).is_ok_and(|hash| Bytes32::from(hash) != coin.puzzle_hash) {
+        notes.push("puzzle reveal does not hash to the coin's puzzle_hash".to_string());
+    }
+    Ok((
+        InputSource::Coin,
+        SpendBundle::new(vec![CoinSpend::new(coin, puzzle, solution)], Default::default()),
+        notes,
+    ))
+}
+
+/// Loads whatever a user pasted, detecting its shape: an `offer1…` string,
+/// spend bundle bytes as hex, or any of the JSON shapes the explicit loaders
+/// accept (mempool blob or item, coin spend list, single coin spend). The
+/// detected format is recorded in the notes.
+pub fn load_autodetected_input(text: &str) -> Result<LoadedInput> {
+    let text = text.trim();
+    let (loaded, format) = if text.starts_with("offer1") {
+        let bundle = decode_offer(text).map_err(|err| anyhow!("failed to decode offer: {err}"))?;
+        ((InputSource::Offer, bundle, Vec::new()), "offer string")
+    } else if !text.starts_with(['{', '[']) {
+        let bundle = parse_spend_bundle_bytes(text)
+            .context("input is not JSON, an offer string, or spend bundle hex")?;
+        ((InputSource::Mempool(None), bundle, Vec::new()), "spend bundle hex")
+    } else {
+        let value: Value = serde_json::from_str(text).context("input looks like JSON but does not parse")?;
+        // A mempool item also carries `removals`, and a spend bundle object
+        // pairs `coin_spends` with its signature; neither is block input.
+        let block_export =
+            value.get("removals").is_some() && value.get("spend_bundle").is_none();
+        let bare_coin_spends = value.get("coin_spends").is_some_and(Value::is_array)
+            && value.get("aggregated_signature").is_none();
+        if value.is_array() || value.get("block_spends").is_some() || block_export || bare_coin_spends {
+            (load_block_spends_input(text)?, "coin spend list")
+        } else if value.get("coin_spend").is_some() || value.get("puzzle_reveal").is_some() {
+            (load_coin_spend_input(text)?, "coin spend")
+        } else {
+            (load_mempool_blob_input(text)?, "mempool blob")
+        }
+    };
+    let (source, bundle, mut notes) = loaded;
+    notes.insert(0, format!("input format autodetected as {format}"));
+    Ok((source, bundle, notes))
+}
+
+/// Runs a block generator (with any referenced generators, in order) to
+/// recover its coin spends. The generator is trusted: puzzles are not
+/// validated here, that happens during inspection.
+pub fn load_generator_input(
+    generator_hex: &str,
+    refs_hex: &[String],
+) -> Result<LoadedInput> {
+    let generator = Program::from(decode_hex(generator_hex).context("invalid generator hex")?);
+    let mut refs = Vec::with_capacity(refs_hex.len());
+    for (idx, ref_hex) in refs_hex.iter().enumerate() {
+        refs.push(decode_hex(ref_hex).with_context(|| format!("invalid generator ref #{idx} hex"))?);
+    }
+
+    let spends = get_coinspends_for_trusted_block(&TEST_CONSTANTS, &generator, &refs, 0)
+        .map_err(|err| anyhow!("failed to run block generator: {err:?}"))?;
+
+    let notes = vec![format!(
+        "block generator with {} ref(s) expanded to {} coin spends; default aggregate signature",
+        refs.len(),
+        spends.len()
+    )];
+    Ok((
+        InputSource::Generator,
+        SpendBundle::new(spends, Default::default()),
+        notes,
+    ))
+}
+
+/// Resolves a block's generator references from `{ "<height>": "<generator
+/// hex>" }`, in the order [`load_generator_input`] expects them. `heights`
+/// is the block's `transactions_generator_ref_list`; when empty, every
+/// mapped generator is used in ascending height order.
+pub fn resolve_generator_refs(refs_json: &str, heights: &[u32]) -> Result<Vec<String>> {
+    let raw: BTreeMap<String, String> =
+        serde_json::from_str(refs_json).context("generator refs must map heights to generator hex")?;
+    let mut by_height = BTreeMap::new();
+    for (height, generator_hex) in raw {
+        let height: u32 = height
+            .trim()
+            .parse()
+            .with_context(|| format!("generator ref key is not a block height: {height}"))?;
+        by_height.insert(height, generator_hex);
+    }
+    if heights.is_empty() {
+        return Ok(by_height.into_values().collect());
+    }
+    heights
+        .iter()
+        .map(|height| {
+            by_height
+                .get(height)
+                .cloned()
+                .with_context(|| format!("no generator given for referenced height {height}"))
+        })
+        .collect()
+}
+
+/// Renders a bundle as canonical spend bundle JSON: every coin spend as
+/// `{coin, puzzle_reveal, solution}` with `0x`-prefixed hex, and the
+/// aggregated signature as compressed `0x` hex. Loaders accept this shape
+/// back unchanged.
+pub fn normalized_spend_bundle_json(bundle: &SpendBundle) -> Value {
+    let coin_spends = bundle
+        .coin_spends
+        .iter()
+        .map(|spend| {
+            json!({
+                "coin": {
+                    "parent_coin_info": encode_hex_prefixed(spend.coin.parent_coin_info.as_ref()),
+                    "puzzle_hash": encode_hex_prefixed(spend.coin.puzzle_hash.as_ref()),
+                    "amount": spend.coin.amount,
+                },
+                "puzzle_reveal": encode_hex_prefixed(spend.puzzle_reveal.as_ref()),
+                "solution": encode_hex_prefixed(spend.solution.as_ref()),
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "coin_spends": coin_spends,
+        "aggregated_signature": encode_hex_prefixed(&bundle.aggregated_signature.to_bytes()),
+    })
+}
+
 fn parse_spend_bundle_object(value: &Value) -> Result<SpendBundle> {
     let normalized = normalize_spend_bundle_value(value)?;
     serde_json::from_value(normalized).context("failed to parse spend bundle JSON")
@@ -143,6 +400,8 @@ fn normalize_coin_spend_value(value: &Value) -> Result<Value> {
                 .as_str()
                 .with_context(|| format!("{k} must be a hex string"))?;
             out.insert(k.clone(), json!(normalize_hex_no_prefix(s)?));
+        } else if k == "coin" {
+            out.insert(k.clone(), normalize_coin_value(v)?);
         } else {
             out.insert(k.clone(), v.clone());
         }
@@ -150,11 +409,27 @@ fn normalize_coin_spend_value(value: &Value) -> Result<Value> {
     Ok(Value::Object(out))
 }
 
+/// Some exports write the coin `amount` as a decimal or `0x` hex string.
+/// Parsed straight to `u64` (never through `f64`) so amounts near the top
+/// of the range stay exact.
+fn normalize_coin_value(coin: &Value) -> Result<Value> {
+    let Some(amount) = coin.get("amount").and_then(Value::as_str) else {
+        return Ok(coin.clone());
+    };
+    let trimmed = amount.trim();
+    let parsed = match trimmed.strip_prefix("0x").or_else(|| trimmed.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => trimmed.parse::<u64>(),
+    };
+    let amount = parsed.map_err(|err| anyhow!("coin amount {amount:?} is not a u64: {err}"))?;
+    let mut coin = coin.clone();
+    coin["amount"] = json!(amount);
+    Ok(coin)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chia_protocol::{Coin, CoinSpend, Program};
-    use clvm_utils::tree_hash_from_bytes;
     use serde_json::json;
 
     fn sample_spend_bundle() -> SpendBundle {
@@ -169,6 +444,42 @@ mod tests {
         SpendBundle::new(vec![spend], Default::default())
     }
 
+    #[test]
+    fn coin_parts_assemble_into_spend() {
+        let bundle = sample_spend_bundle();
+        let spend = &bundle.coin_spends[0];
+        let coin_json = json!({ "coin": spend.coin }).to_string();
+        let (source, parsed, notes) = load_coin_parts_input(
+            &coin_json,
+            &format!("0x{}\n", hex::encode(spend.puzzle_reveal.as_ref())),
+            &hex::encode(spend.solution.as_ref()),
+        )
+        .expect("parse");
+        assert_eq!(source.kind(), "coin");
+        assert_eq!(parsed.coin_spends[0], *spend);
+        assert_eq!(notes.len(), 1);
+    }
+
+    #[test]
+    fn autodetects_hex_and_json_shapes() {
+        let bundle = sample_spend_bundle();
+        let hex_input = format!("0x{}\n", hex::encode(bundle.to_bytes().expect("bytes")));
+        let (source, parsed, notes) = load_autodetected_input(&hex_input).expect("hex");
+        assert_eq!(source.kind(), "mempool_item");
+        assert_eq!(parsed, bundle);
+        assert_eq!(notes[0], "input format autodetected as spend bundle hex");
+
+        let spends = serde_json::to_string(&bundle.coin_spends).expect("json");
+        let (source, _parsed, _notes) = load_autodetected_input(&spends).expect("list");
+        assert_eq!(source.kind(), "block");
+
+        let spend = json!({ "coin_spend": bundle.coin_spends[0] }).to_string();
+        let (source, _parsed, _notes) = load_autodetected_input(&spend).expect("spend");
+        assert_eq!(source.kind(), "coin");
+
+        assert!(load_autodetected_input("not a bundle").is_err());
+    }
+
     #[test]
     fn mempool_wrapper_spend_bundle_parses() {
         let bundle = sample_spend_bundle();
@@ -176,9 +487,91 @@ mod tests {
         let (source, parsed, _notes) =
             load_mempool_blob_input(&serde_json::to_string(&blob).expect("json")).expect("parse");
         assert_eq!(source.kind(), "mempool_item");
+        assert!(matches!(source, InputSource::Mempool(None)));
         assert_eq!(parsed.coin_spends.len(), 1);
     }
 
+    #[test]
+    fn wallet_transaction_wrapper_parses() {
+        let bundle = sample_spend_bundle();
+        let blob = json!({
+            "transaction": { "spend_bundle": bundle, "fee_amount": 50, "name": "0xabcd" },
+            "success": true,
+        });
+        let (source, parsed, notes) =
+            load_mempool_blob_input(&serde_json::to_string(&blob).expect("json")).expect("parse");
+        assert!(matches!(source, InputSource::Mempool(None)));
+        assert_eq!(parsed.coin_spends.len(), 1);
+        assert!(notes.contains(&"transaction reports fee 50 mojos".to_string()));
+        assert!(notes.contains(&"transaction reports name 0xabcd".to_string()));
+    }
+
+    #[test]
+    fn mempool_item_keeps_reported_coins() {
+        let bundle = sample_spend_bundle();
+        let removal = bundle.coin_spends[0].coin;
+        let blob = json!({ "spend_bundle": bundle, "removals": [removal] });
+        let (source, _parsed, _notes) =
+            load_mempool_blob_input(&serde_json::to_string(&blob).expect("json")).expect("parse");
+        let InputSource::Mempool(Some(reported)) = source else {
+            panic!("expected reported coins");
+        };
+        assert_eq!(reported.removals, Some(vec![removal]));
+        assert!(reported.additions.is_none());
+    }
+
+    #[test]
+    fn autodetects_block_exports() {
+        let bundle = sample_spend_bundle();
+        let addition = Coin::new(bundle.coin_spends[0].coin.coin_id(), Bytes32::new([0x22; 32]), 1);
+        let export = json!({ "additions": [addition], "removals": bundle.coin_spends });
+        let (source, parsed, notes) = load_autodetected_input(&export.to_string()).expect("export");
+        assert!(matches!(source, InputSource::Block(Some(_))));
+        assert_eq!(parsed.coin_spends, bundle.coin_spends);
+        assert_eq!(notes[0], "input format autodetected as coin spend list");
+
+        let spends = json!({ "coin_spends": bundle.coin_spends }).to_string();
+        let (source, _parsed, _notes) = load_autodetected_input(&spends).expect("coin_spends");
+        assert_eq!(source.kind(), "block");
+
+        let mempool_item = json!({ "spend_bundle": bundle, "removals": [addition] }).to_string();
+        let (source, _parsed, _notes) = load_autodetected_input(&mempool_item).expect("item");
+        assert_eq!(source.kind(), "mempool_item");
+    }
+
+    #[test]
+    fn block_removals_shape_keeps_additions_for_cross_check() {
+        let bundle = sample_spend_bundle();
+        let addition = Coin::new(bundle.coin_spends[0].coin.coin_id(), Bytes32::new([0x22; 32]), 1);
+        let export = json!({ "additions": [addition], "removals": bundle.coin_spends });
+        let (source, parsed, notes) =
+            load_block_spends_input(&serde_json::to_string(&export).expect("json")).expect("parse");
+        assert_eq!(parsed.coin_spends, bundle.coin_spends);
+        assert!(notes[0].contains("{additions, removals}"));
+        let InputSource::Block(Some(reported)) = source else {
+            panic!("expected reported additions");
+        };
+        assert_eq!(reported.additions, Some(vec![addition]));
+    }
+
+    #[test]
+    fn mempool_dump_keeps_going_past_bad_items() {
+        let bundle = sample_spend_bundle();
+        let dump = json!({
+            "mempool_items": {
+                "0xaa": { "spend_bundle": bundle },
+                "0xbb": { "unexpected": true },
+            },
+            "success": true,
+        });
+        let items =
+            load_mempool_dump_input(&serde_json::to_string(&dump).expect("json")).expect("parse");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].0, "0xaa");
+        assert!(items[0].1.is_ok());
+        assert!(items[1].1.is_err());
+    }
+
     #[test]
     fn mempool_item_wrapper_spend_bundle_parses() {
         let bundle = sample_spend_bundle();
@@ -212,6 +605,54 @@ mod tests {
         assert!(notes.iter().any(|n| n.contains("mempool_item wrapper")));
     }
 
+    #[test]
+    fn spent_context_parses_coin_ids() {
+        let context = format!(
+            r#"{{"0x{}": {{"confirmed_height": 12, "confirmed_timestamp": 1700000000}}}}"#,
+            "ab".repeat(32)
+        );
+        let parsed = load_spent_context(&context).expect("parse");
+        let confirmation = parsed.get(&Bytes32::new([0xab; 32])).expect("entry");
+        assert_eq!(confirmation.confirmed_height, Some(12));
+    }
+
+    #[test]
+    fn normalized_bundle_round_trips() {
+        let bundle = sample_spend_bundle();
+        let normalized = normalized_spend_bundle_json(&bundle);
+        assert_eq!(normalized["coin_spends"][0]["puzzle_reveal"], json!("0x01"));
+        let (_source, parsed, _notes) =
+            load_mempool_blob_input(&normalized.to_string()).expect("parse");
+        assert_eq!(parsed, bundle);
+    }
+
+    #[test]
+    fn string_coin_amounts_parse_exactly() {
+        let spend = &sample_spend_bundle().coin_spends[0];
+        let with_amount = |amount: Value| {
+            let mut value = serde_json::to_value(spend).expect("json");
+            value["coin"]["amount"] = amount;
+            load_coin_spend_input(&value.to_string()).map(|(_, bundle, _)| bundle.coin_spends[0].coin.amount)
+        };
+        assert_eq!(with_amount(json!("18446744073709551615")).expect("decimal"), u64::MAX);
+        assert_eq!(with_amount(json!("0xfffffffffffffffe")).expect("hex"), u64::MAX - 1);
+        assert_eq!(with_amount(json!(1)).expect("number"), 1);
+        let err = with_amount(json!("18446744073709551616")).expect_err("too large");
+        assert!(format!("{err:#}").contains("is not a u64"));
+    }
+
+    #[test]
+    fn generator_refs_resolve_by_height() {
+        let refs = r#"{"200": "0x02", "100": "01"}"#;
+        assert_eq!(resolve_generator_refs(refs, &[]).expect("all"), vec!["01", "0x02"]);
+        assert_eq!(
+            resolve_generator_refs(refs, &[200, 100, 200]).expect("listed"),
+            vec!["0x02", "01", "0x02"]
+        );
+        let err = resolve_generator_refs(refs, &[300]).expect_err("missing");
+        assert!(err.to_string().contains("height 300"));
+    }
+
     #[test]
     fn block_coin_spend_array_parses() {
         let bundle = sample_spend_bundle();
@@ -1,31 +1,62 @@
+use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
+use chia_bls::Signature;
 use chia_consensus::allocator::make_allocator;
-use chia_consensus::consensus_constants::TEST_CONSTANTS;
+use chia_consensus::consensus_constants::{ConsensusConstants, TEST_CONSTANTS};
 use chia_consensus::owned_conditions::{OwnedSpendBundleConditions, OwnedSpendConditions};
 use chia_consensus::spendbundle_conditions::get_conditions_from_spendbundle;
-use chia_protocol::{Bytes, Coin, CoinSpend, SpendBundle};
+use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend, SpendBundle};
 use chialisp::classic::clvm::{OPERATORS_LATEST_VERSION, keyword_from_atom};
 use chialisp::classic::clvm_tools::binutils::disassemble;
-use clvm_utils::tree_hash_from_bytes;
+use clvm_utils::{TreeHash, tree_hash};
 use clvmr::allocator::{Allocator as ClvmAllocator, NodePtr, SExp};
-use clvmr::serde::node_from_bytes_backrefs;
+use clvmr::serde::{node_from_bytes_backrefs, node_to_bytes, node_to_bytes_backrefs};
 use clvmr::LIMIT_HEAP;
-use serde_json::json;
+use serde_json::{Value, json};
 
-use crate::input::InputSource;
-use crate::recognize::recognize_puzzle_and_solution;
+use crate::assets::collect_asset_ids;
+use crate::cache::{RecognitionCache, Recognizer};
+use crate::conditions::{
+    AGG_SIG_ME, AGG_SIG_PARENT, AGG_SIG_UNSAFE, ASSERT_COIN_ANNOUNCEMENT, ASSERT_PUZZLE_ANNOUNCEMENT, CREATE_COIN,
+    CREATE_COIN_ANNOUNCEMENT, CREATE_PUZZLE_ANNOUNCEMENT, RECEIVE_MESSAGE, RESERVE_FEE,
+    ASSERT_BEFORE_HEIGHT_ABSOLUTE, ASSERT_BEFORE_HEIGHT_RELATIVE, ASSERT_BEFORE_SECONDS_ABSOLUTE,
+    ASSERT_BEFORE_SECONDS_RELATIVE, ASSERT_HEIGHT_ABSOLUTE, ASSERT_HEIGHT_RELATIVE,
+    ASSERT_SECONDS_ABSOLUTE, ASSERT_SECONDS_RELATIVE, RawCondition, announcement_id, condition_name, run_puzzle_conditions,
+};
+use crate::coverage::recognition_coverage;
+use crate::display::truncate_disasm;
+use crate::error::InspectError;
+use crate::input::{CoinConfirmation, InputSource, ReportedCoins};
+use crate::keys::{collect_public_keys, recognized_keys};
+use crate::network::Network;
+use crate::offer::check_offer_shape;
+use crate::readiness::{
+    accepted_networks, assess_readiness, check_min_fee, cost_summary, failed_readiness,
+};
+use crate::recognize::{
+    RecognizeOptions, SOURCE_REPO, recognize_with_options, strip_parsed_debug, used_escape_path,
+};
+use crate::registry::CatRegistry;
 use crate::schema::{
-    AggSigInfo, ClvmBehavior, CoinRef, CoinSpendView, ConditionInfo, ConstantBuckets, DynamicBehavior,
-    ErrorInfo, EvaluationInfo, Explanation, FailureInfo, InspectionOutput, InputInfo, NetDelta, NetworkInfo,
-    PuzzleBehavior, PuzzleId, PuzzleInfo, ResultInfo, SignatureSummary, SourceInfo, SpendAnalysis,
-    StaticFeatures, Summary, ToolInfo,
+    AggSigInfo, CanonicalClvm, ClvmBehavior, CoinRef, CoinSpendView, ConditionInfo, ConstantBuckets, DynamicBehavior,
+    EncodedLengths, ErrorInfo, EvaluationInfo, Explanation, FailureInfo, FeeReservation, InspectionOutput, InputInfo, NetDelta, NetworkInfo,
+    PuzzleBehavior, PuzzleId, PuzzleInfo, PuzzleRecognition, Readiness, ReadinessBlocker, ResultInfo,
+    SCHEMA_VERSION, SignatureSummary, SourceInfo,
+    SpendAnalysis, StaticFeatures, Summary, ToolInfo, ValidityWindow, WarningInfo,
 };
+use crate::stream::OutputStream;
 use crate::util::encode_hex_prefixed;
 
-const DEFAULT_MAX_COST: u64 = 11_000_000_000;
+/// CLVM cost ceiling for every puzzle run: one block's worth of cost.
+pub const DEFAULT_MAX_COST: u64 = 11_000_000_000;
 const DEFAULT_PREV_TX_HEIGHT: u32 = 10_000_000;
+/// Share of the bundle's cost, in percent, above which a spend in a
+/// multi-spend bundle is flagged as `HIGH_COST_SPEND`.
+const HIGH_COST_SHARE_PERCENT: u64 = 75;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum ExplainLevel {
@@ -39,57 +70,504 @@ impl Default for ExplainLevel {
     }
 }
 
+/// Ordering of `summary.removals` and `summary.additions`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum CoinOrder {
+    #[default]
+    CoinId,
+    /// Largest amount first, ties broken by coin id.
+    AmountDesc,
+}
+
+impl CoinOrder {
+    fn sort(self, coins: &mut [CoinRef]) {
+        match self {
+            Self::CoinId => coins.sort_by(|a, b| a.coin_id.cmp(&b.coin_id)),
+            Self::AmountDesc => {
+                coins.sort_by(|a, b| b.amount.cmp(&a.amount).then(a.coin_id.cmp(&b.coin_id)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InspectConfig {
+    pub explain_level: ExplainLevel,
+    pub coin_order: CoinOrder,
+    /// Re-serialize puzzle reveals and solutions without backrefs so that
+    /// semantically identical programs produce identical hex.
+    pub canonicalize_backrefs: bool,
+    /// Chain height used to check absolute height timelocks for readiness.
+    pub current_height: Option<u32>,
+    /// Timestamp used to check absolute seconds timelocks for readiness.
+    pub current_timestamp: Option<u64>,
+    /// Include per-operator occurrence counts in `static_features`.
+    pub operator_histogram: bool,
+    /// When set, recognition is structure-only and served from this cache.
+    pub recognition_cache: Option<RecognitionCache>,
+    /// Shared across calls so puzzles seen in earlier bundles recognize
+    /// faster; ignored when `recognition_cache` or `skip_solutions` is set.
+    pub recognizer: Option<Arc<Mutex<Recognizer>>>,
+    pub recognize_options: RecognizeOptions,
+    /// Skip layer recognition entirely; `recognition` keeps its shape but is
+    /// reported as unrecognized.
+    pub skip_recognition: bool,
+    /// Confirmation height/timestamp of spent coins, used together with
+    /// `current_height`/`current_timestamp` to judge relative timelocks.
+    pub spent_context: BTreeMap<Bytes32, CoinConfirmation>,
+    /// Called after each spend is analyzed; the library itself never prints.
+    pub progress: Option<ProgressCallback>,
+    /// Operator set used to disassemble puzzles/solutions and name operators
+    /// in `static_features`; `None` means `OPERATORS_LATEST_VERSION`. Lets
+    /// historical spends render with the operator names of their era.
+    pub operator_version: Option<usize>,
+    /// Condition names (e.g. `AGG_SIG_UNSAFE`, `SOFTFORK`) that fail any
+    /// spend emitting them, and with it the bundle. Matched case-insensitively.
+    pub forbidden_conditions: Vec<String>,
+    /// Names CAT assets: `cat_layer` params gain a `registry` match.
+    pub cat_registry: Option<CatRegistry>,
+    /// Mempool fee rate in mojos per cost; when set, readiness reports the
+    /// minimum fee and blocks bundles paying less.
+    pub fee_per_cost: Option<u64>,
+    /// Networks to validate the bundle against; readiness then lists the
+    /// ones that accept it in `networks_accepted`.
+    pub try_networks: Vec<Network>,
+    /// Attach every field of the consensus `SpendBundleConditions` under
+    /// `result.debug.consensus`.
+    pub dump_consensus: bool,
+    /// Flag any spend costing at least this much as `HIGH_COST_SPEND`, in
+    /// addition to spends dominating a multi-spend bundle's cost.
+    pub high_cost_threshold: Option<u64>,
+    /// Report `result.coverage`: how many spends were fully, partially or
+    /// not recognized.
+    pub coverage: bool,
+    /// Return an error instead of a failed document when consensus rejects
+    /// the bundle, so batch runs stop at the first invalid record.
+    pub fail_fast: bool,
+    /// Fail bundles that are only suspect by default: an empty bundle gets a
+    /// failed result instead of an `EMPTY_BUNDLE` warning.
+    pub strict: bool,
+    /// Truncate puzzle and solution disassembly to this many characters,
+    /// with a marker giving the full length; the hex keeps every byte.
+    pub max_disasm_len: Option<usize>,
+    /// Consensus constants of a simnet or private network (see
+    /// [`constants_from_json`](crate::network::constants_from_json)); `None`
+    /// validates with the offline mainnet constants.
+    pub constants: Option<ConsensusConstants>,
+    /// Replace the chia-wallet-sdk repo and ref stamped into SDK layers'
+    /// `WrapperInfo`, for builds whose provenance the lockfile misreports
+    /// (e.g. a patched or vendored SDK). Other layers keep their source.
+    pub source_repo: Option<String>,
+    pub source_ref: Option<String>,
+}
+
+impl InspectConfig {
+    fn operator_version(&self) -> usize {
+        self.operator_version.unwrap_or(OPERATORS_LATEST_VERSION)
+    }
+
+    fn constants(&self) -> &ConsensusConstants {
+        self.constants.as_ref().unwrap_or(&TEST_CONSTANTS)
+    }
+
+    fn bounded_disasm(&self, disasm: String) -> String {
+        match self.max_disasm_len {
+            Some(max_chars) => truncate_disasm(disasm, max_chars),
+            None => disasm,
+        }
+    }
+}
+
+/// Receives `(spends_done, spends_total)` while a bundle is analyzed.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(usize, usize) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new(callback: impl Fn(usize, usize) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    fn report(&self, done: usize, total: usize) {
+        (self.0)(done, total);
+    }
+}
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+struct TimelockContext {
+    confirmation: Option<CoinConfirmation>,
+    current_height: Option<u32>,
+    current_timestamp: Option<u64>,
+}
+
+impl TimelockContext {
+    /// `None` means the context needed to decide is missing: the current
+    /// height or timestamp for every lock, and the spent coin's confirmation
+    /// for relative locks.
+    fn satisfied(&self, opcode: &str, value: u64) -> Option<bool> {
+        let confirmation = self.confirmation.as_ref();
+        let confirmed_height = confirmation.and_then(|c| c.confirmed_height).map(u64::from);
+        let confirmed_timestamp = confirmation.and_then(|c| c.confirmed_timestamp);
+        let current_height = self.current_height.map(u64::from);
+        match opcode {
+            "ASSERT_HEIGHT_ABSOLUTE" => Some(current_height? >= value),
+            "ASSERT_SECONDS_ABSOLUTE" => Some(self.current_timestamp? >= value),
+            "ASSERT_BEFORE_HEIGHT_ABSOLUTE" => Some(current_height? < value),
+            "ASSERT_BEFORE_SECONDS_ABSOLUTE" => Some(self.current_timestamp? < value),
+            "ASSERT_HEIGHT_RELATIVE" => Some(current_height? >= confirmed_height?.saturating_add(value)),
+            "ASSERT_SECONDS_RELATIVE" => {
+                Some(self.current_timestamp? >= confirmed_timestamp?.saturating_add(value))
+            }
+            "ASSERT_BEFORE_HEIGHT_RELATIVE" => {
+                Some(current_height? < confirmed_height?.saturating_add(value))
+            }
+            "ASSERT_BEFORE_SECONDS_RELATIVE" => {
+                Some(self.current_timestamp? < confirmed_timestamp?.saturating_add(value))
+            }
+            "ASSERT_MY_BIRTH_HEIGHT" => Some(confirmed_height? == value),
+            "ASSERT_MY_BIRTH_SECONDS" => Some(confirmed_timestamp? == value),
+            _ => None,
+        }
+    }
+}
+
+/// Analyzes coin spends without a spend bundle around them, validating with
+/// `network`'s constants. Outputs, fee and conditions come out as for any
+/// bundle; the bundle is built with the default signature, so
+/// `signatures.is_default` is set and readiness reports it unsigned when a
+/// spend requires a signature.
+pub fn analyze_spends(coin_spends: &[CoinSpend], network: Network) -> Result<InspectionOutput> {
+    let mut output = inspect_bundle_with_config(
+        InputSource::Block(None),
+        SpendBundle::new(coin_spends.to_vec(), Signature::default()),
+        vec!["coin spends analyzed without a spend bundle; default aggregate signature".to_string()],
+        &InspectConfig {
+            constants: Some(network.constants()),
+            ..Default::default()
+        },
+    )?;
+    output.network.name = network.name().to_string();
+    Ok(output)
+}
+
 pub fn inspect_bundle(
     source: InputSource,
     spend_bundle: SpendBundle,
     notes: Vec<String>,
     explain_level: ExplainLevel,
 ) -> Result<InspectionOutput> {
+    inspect_bundle_with_config(
+        source,
+        spend_bundle,
+        notes,
+        &InspectConfig {
+            explain_level,
+            ..Default::default()
+        },
+    )
+}
+
+pub fn inspect_bundle_with_config(
+    source: InputSource,
+    spend_bundle: SpendBundle,
+    notes: Vec<String>,
+    config: &InspectConfig,
+) -> Result<InspectionOutput> {
+    check_config(config)?;
     let mut allocator = make_allocator(LIMIT_HEAP);
     let eval = get_conditions_from_spendbundle(
         &mut allocator,
         &spend_bundle,
         DEFAULT_MAX_COST,
         DEFAULT_PREV_TX_HEIGHT,
-        &TEST_CONSTANTS,
+        config.constants(),
     );
+    fail_fast(&eval, config)?;
 
-    match eval {
+    let mut output = match eval {
+        Ok(conditions) => {
+            let owned = OwnedSpendBundleConditions::from(&allocator, conditions);
+            build_success_output(source, notes, spend_bundle, owned, config, None)?
+        }
+        Err(err) => build_error_output(source, notes, spend_bundle, &format!("{err:?}"), config),
+    };
+    finish_output(&mut output, config);
+    Ok(output)
+}
+
+/// Like [`inspect_bundle_with_config`], but writes the output document to
+/// `writer` while it is produced: the header first, each spend as soon as it
+/// is analyzed, then the bundle-level result. Spends are kept only in a
+/// slimmed form (no reveal/solution hex or disassembly) for the bundle-level
+/// checks, so memory stays flat on block-sized inputs.
+///
+/// A streamed spend can't be revised, so a launcher's `singleton_launch`
+/// entry only finds an eve spend that precedes it. When consensus rejects
+/// the bundle, the (failed) document is built in full and then written.
+///
+/// Returns the written document with its spends in slimmed form, enough to
+/// act on `result.status` and the other bundle-level fields.
+pub fn inspect_bundle_streaming(
+    source: InputSource,
+    spend_bundle: SpendBundle,
+    notes: Vec<String>,
+    config: &InspectConfig,
+    writer: &mut dyn Write,
+) -> Result<InspectionOutput> {
+    check_config(config)?;
+    let mut allocator = make_allocator(LIMIT_HEAP);
+    let eval = get_conditions_from_spendbundle(
+        &mut allocator,
+        &spend_bundle,
+        DEFAULT_MAX_COST,
+        DEFAULT_PREV_TX_HEIGHT,
+        config.constants(),
+    );
+    fail_fast(&eval, config)?;
+
+    let mut stream = OutputStream::begin(
+        writer,
+        SCHEMA_VERSION,
+        &tool_info(),
+        &offline_network(config),
+        &input_info(&source, notes.clone()),
+    )?;
+    let mut output = match eval {
         Ok(conditions) => {
             let owned = OwnedSpendBundleConditions::from(&allocator, conditions);
-            Ok(build_success_output(
-                source,
-                notes,
-                spend_bundle,
-                owned,
-                explain_level,
-            ))
+            let mut emit = |spend: &SpendAnalysis| stream.spend(spend);
+            build_success_output(source, notes, spend_bundle, owned, config, Some(&mut emit))?
+        }
+        Err(err) => {
+            let output =
+                build_error_output(source, notes, spend_bundle, &format!("{err:?}"), config);
+            for spend in &output.result.spends {
+                stream.spend(spend)?;
+            }
+            output
+        }
+    };
+    finish_output(&mut output, config);
+    stream.finish(&output.result)?;
+    Ok(output)
+}
+
+fn check_config(config: &InspectConfig) -> Result<()> {
+    if config.operator_version() > OPERATORS_LATEST_VERSION {
+        bail!(
+            "operator version {} is newer than the latest supported ({OPERATORS_LATEST_VERSION})",
+            config.operator_version()
+        );
+    }
+    Ok(())
+}
+
+fn fail_fast<T, E: std::fmt::Debug>(eval: &Result<T, E>, config: &InspectConfig) -> Result<()> {
+    match eval {
+        Err(err) if config.fail_fast => {
+            Err(InspectError::ValidationFailed(format!("{err:?}")).into())
         }
-        Err(err) => Ok(build_error_output(source, notes, spend_bundle, &format!("{err:?}"))),
+        _ => Ok(()),
+    }
+}
+
+/// Bundle-level facts derived from the finished spends.
+fn finish_output(output: &mut InspectionOutput, config: &InspectConfig) {
+    output.result.public_keys = collect_public_keys(output);
+    output.result.asset_ids = collect_asset_ids(output);
+    enforce_forbidden_conditions(output, &config.forbidden_conditions);
+    check_empty_bundle(output, config.strict);
+    if let Some(fee_per_cost) = config.fee_per_cost {
+        check_min_fee(output, fee_per_cost);
+    }
+    if config.coverage {
+        output.result.coverage = Some(recognition_coverage(&output.result.spends));
+    }
+    output.result.digest = output.semantic_digest();
+}
+
+fn tool_info() -> ToolInfo {
+    ToolInfo {
+        name: "chia-inspect".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+fn offline_network(config: &InspectConfig) -> NetworkInfo {
+    let name = if config.constants.is_some() { "custom" } else { "offline" };
+    NetworkInfo {
+        name: name.to_string(),
+        genesis_challenge: Some(encode_hex_prefixed(
+            config.constants().genesis_challenge.as_ref(),
+        )),
+    }
+}
+
+fn input_info(source: &InputSource, notes: Vec<String>) -> InputInfo {
+    InputInfo {
+        source: SourceInfo {
+            kind: source.kind().to_string(),
+            value: None,
+            rpc: None,
+        },
+        notes,
+    }
+}
+
+/// Policy failures on top of consensus: every spend emitting a forbidden
+/// condition is marked failed, and the bundle fails with a
+/// `forbidden_condition` error unless it already failed evaluation.
+fn enforce_forbidden_conditions(output: &mut InspectionOutput, forbidden: &[String]) {
+    if forbidden.is_empty() {
+        return;
+    }
+    let mut offenders = Vec::new();
+    let mut first_offender = None;
+    for (idx, spend) in output.result.spends.iter_mut().enumerate() {
+        let found = forbid_spend(spend, forbidden);
+        if !found.is_empty() {
+            first_offender.get_or_insert(idx);
+            offenders.push(json!({ "spend_index": idx, "conditions": found }));
+        }
+    }
+    let Some(first_offender) = first_offender else {
+        return;
+    };
+
+    let message = format!("{} spend(s) emit forbidden conditions", offenders.len());
+    let result = &mut output.result;
+    result.status = "failed".to_string();
+    if result.error.is_none() {
+        result.error = Some(ErrorInfo {
+            kind: "forbidden_condition".to_string(),
+            message: message.clone(),
+            details: Some(json!({ "spends": offenders })),
+            pointer: Some(format!("{}/evaluation/failure", spend_pointer(first_offender))),
+        });
+    }
+    result.readiness.submittable = false;
+    result.readiness.blockers.push(ReadinessBlocker {
+        kind: "forbidden_condition".to_string(),
+        message,
+    });
+}
+
+/// A bundle without coin spends passes consensus but does nothing, so an
+/// `ok` status would mislead. It always gets an `EMPTY_BUNDLE` warning and,
+/// under `strict`, fails with an `empty_bundle` error.
+fn check_empty_bundle(output: &mut InspectionOutput, strict: bool) {
+    if !output.result.spends.is_empty() {
+        return;
+    }
+    let message = "bundle contains no coin spends".to_string();
+    let result = &mut output.result;
+    result.warnings.push(WarningInfo {
+        code: "EMPTY_BUNDLE".to_string(),
+        message: message.clone(),
+        spend_index: None,
+        details: None,
+        pointer: Some("/result/spends".to_string()),
+    });
+    if !strict {
+        return;
     }
+    result.status = "failed".to_string();
+    if result.error.is_none() {
+        result.error = Some(ErrorInfo {
+            kind: "empty_bundle".to_string(),
+            message: message.clone(),
+            details: None,
+            pointer: Some("/result/spends".to_string()),
+        });
+    }
+    result.readiness.submittable = false;
+    result.readiness.blockers.push(ReadinessBlocker {
+        kind: "empty_bundle".to_string(),
+        message,
+    });
+}
+
+/// Marks one spend failed if it emits a forbidden condition and returns the
+/// offending condition names. Idempotent, so streamed spends can be checked
+/// before they are written and again with the bundle.
+fn forbid_spend(spend: &mut SpendAnalysis, forbidden: &[String]) -> Vec<String> {
+    let found = spend
+        .evaluation
+        .condition_counts
+        .keys()
+        .filter(|name| forbidden.iter().any(|item| item.eq_ignore_ascii_case(name)))
+        .cloned()
+        .collect::<Vec<_>>();
+    if found.is_empty() {
+        return found;
+    }
+    let failure = FailureInfo {
+        kind: "forbidden_condition".to_string(),
+        message: format!("spend emits forbidden condition(s): {}", found.join(", ")),
+    };
+    spend.evaluation.status = "failed".to_string();
+    spend.evaluation.failure = Some(failure.clone());
+    spend.puzzle_behavior.dynamic.status = "failed".to_string();
+    spend.puzzle_behavior.dynamic.failure = Some(failure);
+    found
+}
+
+/// The bundle name the mempool dedups on: sha256 of the streamable
+/// serialization (coin spends followed by the aggregated signature).
+pub fn spend_bundle_id(spend_bundle: &SpendBundle) -> Bytes32 {
+    spend_bundle.name()
 }
 
+/// Receives each spend as soon as it is analyzed, for streaming output.
+type SpendSink<'a> = &'a mut dyn FnMut(&SpendAnalysis) -> Result<()>;
+
+/// With a `sink`, every spend is finalized and handed over as soon as it is
+/// analyzed, and `result.spends` keeps only slimmed copies.
 fn build_success_output(
     source: InputSource,
     notes: Vec<String>,
     spend_bundle: SpendBundle,
     owned: OwnedSpendBundleConditions,
-    explain_level: ExplainLevel,
-) -> InspectionOutput {
+    config: &InspectConfig,
+    mut sink: Option<SpendSink<'_>>,
+) -> Result<InspectionOutput> {
     let mut spends = Vec::<SpendAnalysis>::new();
     let mut removals = Vec::<CoinRef>::new();
     let mut additions = Vec::<CoinRef>::new();
     let mut agg_sig_me = Vec::<AggSigInfo>::new();
     let mut agg_sig_unsafe = Vec::<AggSigInfo>::new();
+    let mut raw_conditions = Vec::<Vec<RawCondition>>::new();
+    let mut clvm_cost = 0_u64;
 
     let spend_count = spend_bundle.coin_spends.len().min(owned.spends.len());
     for idx in 0..spend_count {
         let spend = &spend_bundle.coin_spends[idx];
         let conds = &owned.spends[idx];
-        let spend_analysis = analyze_single_spend(spend, conds, explain_level, &mut agg_sig_me);
+        clvm_cost = clvm_cost.saturating_add(conds.execution_cost + conds.condition_cost);
+        let raw = run_puzzle_conditions(spend.puzzle_reveal.as_ref(), spend.solution.as_ref())
+            .unwrap_or_default();
+        let mut spend_analysis = analyze_single_spend(spend, conds, &raw, config, &mut agg_sig_me);
+        raw_conditions.push(raw);
         removals.push(coin_ref_from_coin(&spend.coin));
         additions.extend(spend_analysis.evaluation.additions.iter().cloned());
-        spends.push(spend_analysis);
+        match sink.as_mut() {
+            Some(sink) => {
+                if let Some(note) = singleton_launch_note(&spend_analysis, &spends) {
+                    spend_analysis.puzzle_behavior.explanation.value_flow.push(note);
+                }
+                forbid_spend(&mut spend_analysis, &config.forbidden_conditions);
+                sink(&spend_analysis)?;
+                spends.push(slim_spend(spend_analysis));
+            }
+            None => spends.push(spend_analysis),
+        }
+        if let Some(progress) = &config.progress {
+            progress.report(idx + 1, spend_count);
+        }
     }
 
     for (pk, msg) in &owned.agg_sig_unsafe {
@@ -99,56 +577,180 @@ fn build_success_output(
         });
     }
 
-    removals.sort_by(|a, b| a.coin_id.cmp(&b.coin_id));
-    additions.sort_by(|a, b| a.coin_id.cmp(&b.coin_id));
-    agg_sig_me.sort_by(|a, b| a.pubkey.cmp(&b.pubkey).then(a.msg.cmp(&b.msg)));
+    if sink.is_none() {
+        annotate_singleton_launches(&mut spends);
+        annotate_did_announcements(&mut spends);
+    }
+
+    // Sorted first so AGG_SIG_UNSAFE warnings can point into the final list.
     agg_sig_unsafe.sort_by(|a, b| a.pubkey.cmp(&b.pubkey).then(a.msg.cmp(&b.msg)));
+    let mut warnings = Vec::<WarningInfo>::new();
+    warn_agg_sig_unsafe(&spend_bundle, &raw_conditions, &agg_sig_unsafe, &mut warnings);
+    warn_timelock_contradictions(&raw_conditions, &mut warnings);
+    warn_overspends(&spends, &mut warnings);
+    warn_high_cost_spends(&spends, config.high_cost_threshold, &mut warnings);
+    warn_foreign_signers(&spends, &mut warnings);
+    warn_reported_coin_mismatch(&source, &removals, &additions, &mut warnings);
+
+    config.coin_order.sort(&mut removals);
+    config.coin_order.sort(&mut additions);
+    agg_sig_me.sort_by(|a, b| a.pubkey.cmp(&b.pubkey).then(a.msg.cmp(&b.msg)));
 
     let fee_mojos = owned
         .removal_amount
         .saturating_sub(owned.addition_amount)
         .try_into()
         .unwrap_or(u64::MAX);
+    let fee_reservation = reconcile_reserve_fee(&raw_conditions, fee_mojos, &mut warnings);
+    let validity_window =
+        validity_window(&spend_bundle, &raw_conditions, &config.spent_context, &mut warnings);
     let net_xch_delta_by_puzzle_hash = compute_net_delta(&removals, &additions);
+    let mut condition_counts = BTreeMap::<String, u64>::new();
+    for spend in &spends {
+        for (opcode, count) in &spend.evaluation.condition_counts {
+            *condition_counts.entry(opcode.clone()).or_insert(0) += count;
+        }
+    }
+    let mut readiness = assess_readiness(&spend_bundle, &owned, config.constants(), config);
+    if !config.try_networks.is_empty() {
+        readiness.networks_accepted =
+            Some(accepted_networks(&spend_bundle, &owned, &config.try_networks));
+    }
+    let offer = check_offer_shape(&spends);
+    let debug = config
+        .dump_consensus
+        .then(|| json!({ "consensus": consensus_conditions_json(&owned) }));
 
-    InspectionOutput {
-        schema_version: "chia.inspect.spendbundle.v2".to_string(),
-        tool: ToolInfo {
-            name: "chia-inspect".to_string(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
-        },
-        network: NetworkInfo {
-            name: "offline".to_string(),
-            genesis_challenge: Some(encode_hex_prefixed(TEST_CONSTANTS.genesis_challenge.as_ref())),
-        },
-        input: InputInfo {
-            source: SourceInfo {
-                kind: source.kind().to_string(),
-                value: None,
-                rpc: None,
-            },
-            notes,
-        },
+    Ok(InspectionOutput {
+        schema_version: SCHEMA_VERSION.to_string(),
+        tool: tool_info(),
+        network: offline_network(config),
+        input: input_info(&source, notes),
         result: ResultInfo {
             status: "ok".to_string(),
+            bundle_id: encode_hex_prefixed(spend_bundle_id(&spend_bundle).as_ref()),
+            digest: String::new(),
             error: None,
             summary: Summary {
                 removals,
                 additions,
                 fee_mojos,
                 net_xch_delta_by_puzzle_hash,
+                condition_counts,
+                cost: cost_summary(serialized_size(&spend_bundle), clvm_cost, config.constants()),
+                fee_reservation,
+                validity_window,
             },
             spends,
             signatures: SignatureSummary {
                 aggregated_signature: encode_hex_prefixed(
                     &spend_bundle.aggregated_signature.to_bytes(),
                 ),
+                is_default: spend_bundle.aggregated_signature == Signature::default(),
                 agg_sig_me,
                 agg_sig_unsafe,
             },
-            offer: None,
+            offer,
+            readiness,
+            warnings,
+            public_keys: BTreeSet::new(),
+            asset_ids: BTreeSet::new(),
+            coverage: None,
+            debug,
         },
-    }
+    })
+}
+
+/// Every field of the consensus conditions, unfiltered and in consensus
+/// order, so the schema's mapping can be checked against the source.
+fn consensus_conditions_json(owned: &OwnedSpendBundleConditions) -> Value {
+    let sigs = |sigs: &[(chia_bls::PublicKey, Bytes)]| {
+        sigs.iter()
+            .map(|(pk, msg)| {
+                json!([encode_hex_prefixed(&pk.to_bytes()), encode_hex_prefixed(msg.as_ref())])
+            })
+            .collect::<Vec<_>>()
+    };
+    let spends = owned
+        .spends
+        .iter()
+        .map(|spend| {
+            json!({
+                "coin_id": encode_hex_prefixed(spend.coin_id.as_ref()),
+                "parent_id": encode_hex_prefixed(spend.parent_id.as_ref()),
+                "puzzle_hash": encode_hex_prefixed(spend.puzzle_hash.as_ref()),
+                "coin_amount": spend.coin_amount,
+                "height_relative": spend.height_relative,
+                "seconds_relative": spend.seconds_relative,
+                "before_height_relative": spend.before_height_relative,
+                "before_seconds_relative": spend.before_seconds_relative,
+                "birth_height": spend.birth_height,
+                "birth_seconds": spend.birth_seconds,
+                "create_coin": spend
+                    .create_coin
+                    .iter()
+                    .map(|(puzzle_hash, amount, hint)| {
+                        let hint = hint.as_ref().map(|hint| encode_hex_prefixed(hint.as_ref()));
+                        json!([encode_hex_prefixed(puzzle_hash.as_ref()), amount, hint])
+                    })
+                    .collect::<Vec<_>>(),
+                "agg_sig_me": sigs(&spend.agg_sig_me),
+                "agg_sig_parent": sigs(&spend.agg_sig_parent),
+                "agg_sig_puzzle": sigs(&spend.agg_sig_puzzle),
+                "agg_sig_amount": sigs(&spend.agg_sig_amount),
+                "agg_sig_puzzle_amount": sigs(&spend.agg_sig_puzzle_amount),
+                "agg_sig_parent_amount": sigs(&spend.agg_sig_parent_amount),
+                "agg_sig_parent_puzzle": sigs(&spend.agg_sig_parent_puzzle),
+                "flags": spend.flags,
+                "execution_cost": spend.execution_cost,
+                "condition_cost": spend.condition_cost,
+                "fingerprint": encode_hex_prefixed(spend.fingerprint.as_ref()),
+            })
+        })
+        .collect::<Vec<_>>();
+    // u128 amounts are rendered as strings; JSON numbers lose precision
+    // past 2^53 in most consumers.
+    json!({
+        "spends": spends,
+        "reserve_fee": owned.reserve_fee,
+        "height_absolute": owned.height_absolute,
+        "seconds_absolute": owned.seconds_absolute,
+        "before_height_absolute": owned.before_height_absolute,
+        "before_seconds_absolute": owned.before_seconds_absolute,
+        "agg_sig_unsafe": sigs(&owned.agg_sig_unsafe),
+        "cost": owned.cost,
+        "removal_amount": owned.removal_amount.to_string(),
+        "addition_amount": owned.addition_amount.to_string(),
+        "validated_signature": owned.validated_signature,
+        "execution_cost": owned.execution_cost,
+        "condition_cost": owned.condition_cost,
+    })
+}
+
+/// Bytes of puzzle reveals and solutions, the part of a spend that is
+/// charged per byte.
+fn serialized_size(spend_bundle: &SpendBundle) -> u64 {
+    spend_bundle
+        .coin_spends
+        .iter()
+        .map(|spend| (spend.puzzle_reveal.len() + spend.solution.len()) as u64)
+        .sum()
+}
+
+/// Drops the bulky per-spend fields (reveal/solution hex and disassembly)
+/// that no bundle-level check reads.
+fn slim_spend(mut spend: SpendAnalysis) -> SpendAnalysis {
+    spend.coin_spend.puzzle_reveal.clear();
+    spend.coin_spend.solution.clear();
+    spend.puzzle.puzzle_reveal_disasm.clear();
+    spend.puzzle.solution_disasm.clear();
+    let clvm = &mut spend.puzzle_behavior.clvm;
+    clvm.puzzle_reveal_bytes.clear();
+    clvm.solution_bytes.clear();
+    clvm.puzzle_opd.clear();
+    clvm.solution_opd.clear();
+    clvm.canonical = None;
+    spend
 }
 
 fn build_error_output(
@@ -156,18 +758,45 @@ fn build_error_output(
     notes: Vec<String>,
     spend_bundle: SpendBundle,
     message: &str,
+    config: &InspectConfig,
 ) -> InspectionOutput {
     let mut spends = Vec::new();
     let mut removals = Vec::new();
+    let mut raw_conditions = Vec::new();
     for spend in &spend_bundle.coin_spends {
         removals.push(coin_ref_from_coin(&spend.coin));
-        let (puzzle_disasm, static_features, uses_backrefs) = analyze_clvm_bytes(spend.puzzle_reveal.as_ref());
-        let (solution_disasm, _, _) = analyze_clvm_bytes(spend.solution.as_ref());
-        let recognition =
-            recognize_puzzle_and_solution(spend.puzzle_reveal.as_ref(), spend.solution.as_ref());
-        let puzzle_hash = tree_hash_from_bytes(spend.puzzle_reveal.as_ref())
-            .map(|h| encode_hex_prefixed(h.as_ref()))
-            .unwrap_or_else(|_| encode_hex_prefixed(spend.coin.puzzle_hash.as_ref()));
+        raw_conditions.push(
+            run_puzzle_conditions(spend.puzzle_reveal.as_ref(), spend.solution.as_ref())
+                .unwrap_or_default(),
+        );
+        let ClvmAnalysis {
+            disasm: puzzle_disasm,
+            features: static_features,
+            uses_backrefs,
+            tree_hash: puzzle_tree_hash,
+        } = analyze_clvm_bytes(
+            spend.puzzle_reveal.as_ref(),
+            config.operator_histogram,
+            config.operator_version(),
+        );
+        let puzzle_disasm = config.bounded_disasm(puzzle_disasm);
+        let solution_disasm = config.bounded_disasm(
+            analyze_clvm_bytes(spend.solution.as_ref(), false, config.operator_version()).disasm,
+        );
+        let recognition = recognize_spend(spend, config);
+        let escaped = used_escape_path(&recognition);
+        // Consensus rejected the bundle, so read the would-be outputs from the
+        // re-run. Negative amounts (the CAT melt sentinel) are not outputs.
+        let created = raw_conditions
+            .last()
+            .into_iter()
+            .flatten()
+            .filter(|condition| condition.opcode == CREATE_COIN)
+            .filter_map(|condition| condition.args.get(1).and_then(|amount| atom_to_u64(amount)));
+        let value_balance = value_balance(spend.coin.amount, created);
+        let puzzle_hash = encode_hex_prefixed(
+            puzzle_tree_hash.map_or(spend.coin.puzzle_hash, Bytes32::from).as_ref(),
+        );
 
         spends.push(SpendAnalysis {
             coin_spend: CoinSpendView {
@@ -192,6 +821,7 @@ fn build_error_output(
                 additions: Vec::new(),
                 announcements: Vec::new(),
                 assertions: Vec::new(),
+                condition_counts: BTreeMap::new(),
                 failure: Some(FailureInfo {
                     kind: "validation_error".to_string(),
                     message: message.to_string(),
@@ -205,6 +835,13 @@ fn build_error_output(
                     solution_opd: solution_disasm,
                     uses_backrefs,
                     serialized_len_bytes: spend.puzzle_reveal.len(),
+                    puzzle_len: encoded_lengths(spend.puzzle_reveal.as_ref()),
+                    solution_len: encoded_lengths(spend.solution.as_ref()),
+                    canonical: if config.canonicalize_backrefs {
+                        canonicalize_clvm(spend)
+                    } else {
+                        None
+                    },
                 },
                 static_features,
                 dynamic: DynamicBehavior {
@@ -219,49 +856,62 @@ fn build_error_output(
                 },
                 explanation: Explanation::default(),
             },
+            used_escape_path: escaped,
+            value_balance,
+            requires_signature: raw_conditions.last().is_some_and(|raw| requires_signature(raw)),
         });
     }
 
+    let mut warnings = Vec::new();
+    warn_timelock_contradictions(&raw_conditions, &mut warnings);
+    let validity_window =
+        validity_window(&spend_bundle, &raw_conditions, &config.spent_context, &mut warnings);
+
     InspectionOutput {
-        schema_version: "chia.inspect.spendbundle.v2".to_string(),
-        tool: ToolInfo {
-            name: "chia-inspect".to_string(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
-        },
-        network: NetworkInfo {
-            name: "offline".to_string(),
-            genesis_challenge: Some(encode_hex_prefixed(TEST_CONSTANTS.genesis_challenge.as_ref())),
-        },
-        input: InputInfo {
-            source: SourceInfo {
-                kind: source.kind().to_string(),
-                value: None,
-                rpc: None,
-            },
-            notes,
-        },
+        schema_version: SCHEMA_VERSION.to_string(),
+        tool: tool_info(),
+        network: offline_network(config),
+        input: input_info(&source, notes),
         result: ResultInfo {
             status: "failed".to_string(),
+            bundle_id: encode_hex_prefixed(spend_bundle_id(&spend_bundle).as_ref()),
+            digest: String::new(),
             error: Some(ErrorInfo {
                 kind: "validation_error".to_string(),
                 message: message.to_string(),
                 details: None,
+                // Consensus errors don't say which spend failed.
+                pointer: Some("/result/spends".to_string()),
             }),
             summary: Summary {
                 removals,
                 additions: Vec::new(),
                 fee_mojos: 0,
                 net_xch_delta_by_puzzle_hash: Vec::new(),
+                condition_counts: BTreeMap::new(),
+                cost: cost_summary(serialized_size(&spend_bundle), 0, config.constants()),
+                fee_reservation: None,
+                validity_window,
             },
             spends,
             signatures: SignatureSummary {
                 aggregated_signature: encode_hex_prefixed(
                     &spend_bundle.aggregated_signature.to_bytes(),
                 ),
+                is_default: spend_bundle.aggregated_signature == Signature::default(),
                 agg_sig_me: Vec::new(),
                 agg_sig_unsafe: Vec::new(),
             },
             offer: None,
+            readiness: Readiness {
+                networks_accepted: (!config.try_networks.is_empty()).then(Vec::new),
+                ..failed_readiness(message)
+            },
+            warnings,
+            public_keys: BTreeSet::new(),
+            asset_ids: BTreeSet::new(),
+            coverage: None,
+            debug: None,
         },
     }
 }
@@ -269,13 +919,27 @@ fn build_error_output(
 fn analyze_single_spend(
     spend: &CoinSpend,
     conds: &OwnedSpendConditions,
-    explain_level: ExplainLevel,
+    raw_conditions: &[RawCondition],
+    config: &InspectConfig,
     agg_sig_me_out: &mut Vec<AggSigInfo>,
 ) -> SpendAnalysis {
     let coin_ref = coin_ref_from_coin(&spend.coin);
-    let (puzzle_disasm, static_features, uses_backrefs) = analyze_clvm_bytes(spend.puzzle_reveal.as_ref());
-    let (solution_disasm, _, _) = analyze_clvm_bytes(spend.solution.as_ref());
-    let recognition = recognize_puzzle_and_solution(spend.puzzle_reveal.as_ref(), spend.solution.as_ref());
+    let ClvmAnalysis {
+        disasm: puzzle_disasm,
+        features: static_features,
+        uses_backrefs,
+        tree_hash: puzzle_tree_hash,
+    } = analyze_clvm_bytes(
+        spend.puzzle_reveal.as_ref(),
+        config.operator_histogram,
+        config.operator_version(),
+    );
+    let puzzle_disasm = config.bounded_disasm(puzzle_disasm);
+    let solution_disasm = config.bounded_disasm(
+        analyze_clvm_bytes(spend.solution.as_ref(), false, config.operator_version()).disasm,
+    );
+    let recognition = recognize_spend(spend, config);
+    let escaped = used_escape_path(&recognition);
 
     let mut create_coin = conds.create_coin.clone();
     create_coin.sort_by(|a, b| {
@@ -339,6 +1003,7 @@ fn analyze_single_spend(
         &mut Vec::new(),
     );
 
+    let refresh_fee = refresh_fee(&spend.coin, &create_coin);
     for (puzzle_hash, amount, hint) in create_coin {
         let new_coin = Coin::new(conds.coin_id, puzzle_hash, amount);
         let coin_ref = coin_ref_from_coin(&new_coin);
@@ -361,50 +1026,102 @@ fn analyze_single_spend(
         }));
     }
 
+    if let Some(note) = singleton_recreation(&spend.coin, &recognition, &additions) {
+        explanation.value_flow.push(note);
+    }
+
+    let timelocks = TimelockContext {
+        confirmation: config.spent_context.get(&spend.coin.coin_id()).cloned(),
+        current_height: config.current_height,
+        current_timestamp: config.current_timestamp,
+    };
     add_optional_assertion(
         "ASSERT_HEIGHT_RELATIVE",
         conds.height_relative.map(u64::from),
         &mut conditions,
         &mut explanation,
+        &timelocks,
     );
     add_optional_assertion(
         "ASSERT_SECONDS_RELATIVE",
         conds.seconds_relative,
         &mut conditions,
         &mut explanation,
+        &timelocks,
     );
     add_optional_assertion(
         "ASSERT_BEFORE_HEIGHT_RELATIVE",
         conds.before_height_relative.map(u64::from),
         &mut conditions,
         &mut explanation,
+        &timelocks,
     );
     add_optional_assertion(
         "ASSERT_BEFORE_SECONDS_RELATIVE",
         conds.before_seconds_relative,
         &mut conditions,
         &mut explanation,
+        &timelocks,
     );
     add_optional_assertion(
         "ASSERT_MY_BIRTH_HEIGHT",
         conds.birth_height.map(u64::from),
         &mut conditions,
         &mut explanation,
+        &timelocks,
     );
     add_optional_assertion(
         "ASSERT_MY_BIRTH_SECONDS",
         conds.birth_seconds,
         &mut conditions,
         &mut explanation,
+        &timelocks,
     );
+    // Consensus folds absolute locks into the bundle, so each spend's own
+    // tightest bound is read from its raw conditions.
+    let later: fn(u64, u64) -> u64 = u64::max;
+    let earlier: fn(u64, u64) -> u64 = u64::min;
+    let absolute = [
+        ("ASSERT_HEIGHT_ABSOLUTE", ASSERT_HEIGHT_ABSOLUTE, later),
+        ("ASSERT_SECONDS_ABSOLUTE", ASSERT_SECONDS_ABSOLUTE, later),
+        ("ASSERT_BEFORE_HEIGHT_ABSOLUTE", ASSERT_BEFORE_HEIGHT_ABSOLUTE, earlier),
+        ("ASSERT_BEFORE_SECONDS_ABSOLUTE", ASSERT_BEFORE_SECONDS_ABSOLUTE, earlier),
+    ];
+    for (name, opcode, tighter) in absolute {
+        add_optional_assertion(
+            name,
+            bound(raw_conditions, opcode, tighter),
+            &mut conditions,
+            &mut explanation,
+            &timelocks,
+        );
+    }
+
+    let (announcements, assertions) = collect_announcements(&spend.coin, raw_conditions);
+
+    if is_glue_spend(raw_conditions) {
+        explanation.role = Some("glue".to_string());
+        explanation.value_flow.push(json!({
+            "action": "none",
+            "note": "creates no coins; exists only to link the bundle through announcements, messages or concurrent-spend assertions",
+            "amount_to_fee": spend.coin.amount,
+        }));
+    } else if let Some(amount_to_fee) = refresh_fee {
+        explanation.role = Some("refresh".to_string());
+        explanation.value_flow.push(json!({
+            "action": "refresh",
+            "note": "recreates the spent coin's puzzle hash and amount (less any fee); no value moves",
+            "amount_to_fee": amount_to_fee,
+        }));
+    }
 
-    if explain_level == ExplainLevel::Conditions {
+    if config.explain_level == ExplainLevel::Conditions {
         explanation.constraints.clear();
     }
 
-    let puzzle_hash = tree_hash_from_bytes(spend.puzzle_reveal.as_ref())
-        .map(|h| encode_hex_prefixed(h.as_ref()))
-        .unwrap_or_else(|_| encode_hex_prefixed(spend.coin.puzzle_hash.as_ref()));
+    let puzzle_hash = encode_hex_prefixed(
+        puzzle_tree_hash.map_or(spend.coin.puzzle_hash, Bytes32::from).as_ref(),
+    );
 
     let puzzle_behavior = PuzzleBehavior {
         clvm: ClvmBehavior {
@@ -414,6 +1131,13 @@ fn analyze_single_spend(
             solution_opd: solution_disasm.clone(),
             uses_backrefs,
             serialized_len_bytes: spend.puzzle_reveal.len(),
+            puzzle_len: encoded_lengths(spend.puzzle_reveal.as_ref()),
+            solution_len: encoded_lengths(spend.solution.as_ref()),
+            canonical: if config.canonicalize_backrefs {
+                canonicalize_clvm(spend)
+            } else {
+                None
+            },
         },
         static_features,
         dynamic: DynamicBehavior {
@@ -425,6 +1149,7 @@ fn analyze_single_spend(
         },
         explanation,
     };
+    let value_balance = value_balance(spend.coin.amount, additions.iter().map(|coin| coin.amount));
 
     SpendAnalysis {
         coin_spend: CoinSpendView {
@@ -447,11 +1172,88 @@ fn analyze_single_spend(
             cost: conds.execution_cost + conds.condition_cost,
             conditions,
             additions,
-            announcements: Vec::new(),
-            assertions: Vec::new(),
+            announcements,
+            assertions,
+            condition_counts: count_conditions(raw_conditions),
             failure: None,
         },
         puzzle_behavior,
+        used_escape_path: escaped,
+        value_balance,
+        requires_signature: requires_signature(raw_conditions),
+    }
+}
+
+/// Whether any of a spend's conditions is an AGG_SIG_* kind.
+fn requires_signature(raw_conditions: &[RawCondition]) -> bool {
+    raw_conditions
+        .iter()
+        .any(|condition| (AGG_SIG_PARENT..=AGG_SIG_ME).contains(&condition.opcode))
+}
+
+/// Created minus consumed amount for one spend: its contribution to the
+/// fee for XCH, or to the CAT subtotal for CAT spends.
+fn value_balance(consumed: u64, created: impl IntoIterator<Item = u64>) -> i128 {
+    created.into_iter().map(i128::from).sum::<i128>() - i128::from(consumed)
+}
+
+fn recognize_spend(spend: &CoinSpend, config: &InspectConfig) -> PuzzleRecognition {
+    if config.skip_recognition {
+        return PuzzleRecognition {
+            recognized: false,
+            candidates: Vec::new(),
+            wrappers: Vec::new(),
+            parsed_solution: None,
+            inner_most_layer: None,
+            unrecognized_curried: None,
+        };
+    }
+    let mut recognition = match (&config.recognition_cache, &config.recognizer) {
+        (Some(cache), _) => cache.recognize_structure(spend.puzzle_reveal.as_ref()),
+        (None, Some(recognizer)) if !config.recognize_options.skip_solutions => {
+            let mut recognition = recognizer
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .recognize(spend.puzzle_reveal.as_ref(), spend.solution.as_ref());
+            if config.recognize_options.compact {
+                strip_parsed_debug(&mut recognition);
+            }
+            recognition
+        }
+        _ => recognize_with_options(
+            spend.puzzle_reveal.as_ref(),
+            spend.solution.as_ref(),
+            &config.recognize_options,
+        ),
+    };
+    if let Some(registry) = &config.cat_registry {
+        registry.annotate(&mut recognition);
+    }
+    override_sdk_source(&mut recognition, config);
+    recognition
+}
+
+fn override_sdk_source(recognition: &mut PuzzleRecognition, config: &InspectConfig) {
+    if config.source_repo.is_none() && config.source_ref.is_none() {
+        return;
+    }
+    for wrapper in &mut recognition.wrappers {
+        if wrapper.source_repo != SOURCE_REPO {
+            continue;
+        }
+        if let Some(source_ref) = &config.source_ref {
+            wrapper.source_ref.clone_from(source_ref);
+        }
+        if let Some(source_repo) = &config.source_repo {
+            wrapper.source_repo.clone_from(source_repo);
+        }
+    }
+    if let Some(source_repo) = &config.source_repo {
+        for candidate in &mut recognition.candidates {
+            if candidate.source_repo.as_deref() == Some(SOURCE_REPO) {
+                candidate.source_repo = Some(source_repo.clone());
+            }
+        }
     }
 }
 
@@ -483,11 +1285,686 @@ fn add_signature_conditions(
     }
 }
 
+/// Consensus only reports AGG_SIG_UNSAFE bundle-wide, so the raw conditions
+/// are used to attribute each pubkey/message pair back to its spend.
+fn warn_agg_sig_unsafe(
+    spend_bundle: &SpendBundle,
+    raw_conditions: &[Vec<RawCondition>],
+    agg_sig_unsafe: &[AggSigInfo],
+    warnings: &mut Vec<WarningInfo>,
+) {
+    for (sig_idx, sig) in agg_sig_unsafe.iter().enumerate() {
+        let spend_index = raw_conditions.iter().position(|conditions| {
+            conditions.iter().any(|condition| {
+                condition.opcode == AGG_SIG_UNSAFE
+                    && condition.args.len() >= 2
+                    && encode_hex_prefixed(&condition.args[0]) == sig.pubkey
+                    && encode_hex_prefixed(&condition.args[1]) == sig.msg
+            })
+        });
+        warnings.push(WarningInfo {
+            code: "AGG_SIG_UNSAFE".to_string(),
+            message: "AGG_SIG_UNSAFE does not bind the signature to a coin; the same signature can be replayed by any spend that emits this pubkey/message pair".to_string(),
+            spend_index,
+            details: Some(json!({
+                "pubkey": sig.pubkey,
+                "msg": sig.msg,
+                "coin_id": spend_index.map(|idx| {
+                    encode_hex_prefixed(spend_bundle.coin_spends[idx].coin.coin_id().as_ref())
+                }),
+            })),
+            pointer: Some(format!("/result/signatures/agg_sig_unsafe/{sig_idx}")),
+        });
+    }
+}
+
+/// Consensus rejects impossible windows outright, so this is mostly what
+/// explains a failed evaluation; it is computed from the raw conditions so
+/// it works on both the success and the failure path.
+fn warn_timelock_contradictions(raw_conditions: &[Vec<RawCondition>], warnings: &mut Vec<WarningInfo>) {
+    let windows = [
+        ("seconds_relative", ASSERT_SECONDS_RELATIVE, ASSERT_BEFORE_SECONDS_RELATIVE),
+        ("height_relative", ASSERT_HEIGHT_RELATIVE, ASSERT_BEFORE_HEIGHT_RELATIVE),
+    ];
+    for (idx, conditions) in raw_conditions.iter().enumerate() {
+        for (window, after, before) in windows {
+            push_contradiction(
+                window,
+                bound(conditions, after, u64::max),
+                bound(conditions, before, u64::min),
+                Some(idx),
+                Some(spend_pointer(idx)),
+                warnings,
+            );
+        }
+    }
+
+    // Absolute timelocks apply to the whole bundle, whichever spend emits them.
+    let all = raw_conditions.iter().flatten().cloned().collect::<Vec<_>>();
+    let absolute = [
+        ("seconds_absolute", ASSERT_SECONDS_ABSOLUTE, ASSERT_BEFORE_SECONDS_ABSOLUTE),
+        ("height_absolute", ASSERT_HEIGHT_ABSOLUTE, ASSERT_BEFORE_HEIGHT_ABSOLUTE),
+    ];
+    for (window, after, before) in absolute {
+        let before_bound = bound(&all, before, u64::min);
+        // Points at the spend emitting the tightest `before` bound.
+        let emitter = raw_conditions.iter().position(|conditions| {
+            before_bound.is_some() && bound(conditions, before, u64::min) == before_bound
+        });
+        push_contradiction(
+            window,
+            bound(&all, after, u64::max),
+            before_bound,
+            None,
+            emitter.map(spend_pointer),
+            warnings,
+        );
+    }
+}
+
+/// Intersects every spend's timelocks into the bundle's absolute validity
+/// window. Relative locks count from the spent coin's confirmation, so they
+/// are only placed when `spent_context` has it. An empty window is warned
+/// about unless a TIMELOCK_CONTRADICTION already explains it.
+fn validity_window(
+    spend_bundle: &SpendBundle,
+    raw_conditions: &[Vec<RawCondition>],
+    spent_context: &BTreeMap<Bytes32, CoinConfirmation>,
+    warnings: &mut Vec<WarningInfo>,
+) -> Option<ValidityWindow> {
+    let mut window = ValidityWindow {
+        not_before_height: None,
+        not_before_seconds: None,
+        before_height: None,
+        before_seconds: None,
+        satisfiable: true,
+        unresolved_relative: Vec::new(),
+    };
+    let mut constrained = false;
+    for (idx, conditions) in raw_conditions.iter().enumerate() {
+        let confirmation = spend_bundle
+            .coin_spends
+            .get(idx)
+            .and_then(|spend| spent_context.get(&spend.coin.coin_id()));
+        let confirmed_height = confirmation.and_then(|c| c.confirmed_height).map(u64::from);
+        let confirmed_seconds = confirmation.and_then(|c| c.confirmed_timestamp);
+        let mut unresolved = false;
+        let later: fn(u64, u64) -> u64 = u64::max;
+        let earlier: fn(u64, u64) -> u64 = u64::min;
+        let bounds = [
+            (&mut window.not_before_height, ASSERT_HEIGHT_ABSOLUTE, ASSERT_HEIGHT_RELATIVE, confirmed_height, later),
+            (&mut window.not_before_seconds, ASSERT_SECONDS_ABSOLUTE, ASSERT_SECONDS_RELATIVE, confirmed_seconds, later),
+            (&mut window.before_height, ASSERT_BEFORE_HEIGHT_ABSOLUTE, ASSERT_BEFORE_HEIGHT_RELATIVE, confirmed_height, earlier),
+            (&mut window.before_seconds, ASSERT_BEFORE_SECONDS_ABSOLUTE, ASSERT_BEFORE_SECONDS_RELATIVE, confirmed_seconds, earlier),
+        ];
+        for (slot, absolute, relative, origin, tighter) in bounds {
+            let absolute = bound(conditions, absolute, tighter);
+            let relative = bound(conditions, relative, tighter);
+            constrained |= absolute.is_some() || relative.is_some();
+            let placed = match (relative, origin) {
+                (Some(offset), Some(origin)) => Some(origin.saturating_add(offset)),
+                (Some(_), None) => {
+                    unresolved = true;
+                    None
+                }
+                (None, _) => None,
+            };
+            for value in [absolute, placed].into_iter().flatten() {
+                *slot = Some(slot.map_or(value, |current| tighter(current, value)));
+            }
+        }
+        if unresolved {
+            window.unresolved_relative.push(idx);
+        }
+    }
+    if !constrained {
+        return None;
+    }
+
+    let empty = |not_before: Option<u64>, before: Option<u64>| {
+        not_before.zip(before).is_some_and(|(not_before, before)| not_before >= before)
+    };
+    window.satisfiable = !empty(window.not_before_height, window.before_height)
+        && !empty(window.not_before_seconds, window.before_seconds);
+    let explained = warnings.iter().any(|warning| warning.code == "TIMELOCK_CONTRADICTION");
+    if !window.satisfiable && !explained {
+        warnings.push(WarningInfo {
+            code: "VALIDITY_WINDOW_EMPTY".to_string(),
+            message: "the spends' timelocks leave no height or time at which the whole bundle is valid".to_string(),
+            spend_index: None,
+            details: Some(json!(window)),
+            pointer: Some("/result/summary/validity_window".to_string()),
+        });
+    }
+    Some(window)
+}
+
+/// Sums RESERVE_FEE across spends the way the mempool does and compares the
+/// total with the bundle's implied fee. Every reservation may be satisfiable
+/// on its own while their sum is not, which only shows at this level.
+fn reconcile_reserve_fee(
+    raw_conditions: &[Vec<RawCondition>],
+    implied_fee: u64,
+    warnings: &mut Vec<WarningInfo>,
+) -> Option<FeeReservation> {
+    let mut reserved_total = 0_u64;
+    let mut spend_indices = Vec::new();
+    for (idx, conditions) in raw_conditions.iter().enumerate() {
+        let reserved = conditions
+            .iter()
+            .filter(|condition| condition.opcode == RESERVE_FEE)
+            .filter_map(|condition| condition.args.first().and_then(|arg| atom_to_u64(arg)))
+            .collect::<Vec<_>>();
+        if reserved.is_empty() {
+            continue;
+        }
+        spend_indices.push(idx);
+        reserved_total = reserved.into_iter().fold(reserved_total, u64::saturating_add);
+    }
+    if spend_indices.is_empty() {
+        return None;
+    }
+    if reserved_total > implied_fee {
+        warnings.push(WarningInfo {
+            code: "RESERVE_FEE_EXCEEDS_FEE".to_string(),
+            message: format!("spends reserve {reserved_total} mojos but the fee is {implied_fee}"),
+            spend_index: None,
+            details: Some(json!({
+                "reserved_total": reserved_total,
+                "implied_fee": implied_fee,
+                "spend_indices": spend_indices,
+            })),
+            pointer: Some("/result/summary/fee_reservation".to_string()),
+        });
+    }
+    Some(FeeReservation {
+        reserved_total,
+        implied_fee,
+        spend_indices,
+    })
+}
+
+/// Flags spends whose CREATE_COIN outputs exceed the coin they spend. The
+/// fee balance is bundle-wide, so an overspend is only legitimate when other
+/// spends fund it: CAT spends balance through the ring, and spends linked by
+/// announcements are the usual "one coin pays for all" wallet shape. Melt
+/// sentinels never reach the evaluated additions, so they aren't counted.
+fn warn_overspends(spends: &[SpendAnalysis], warnings: &mut Vec<WarningInfo>) {
+    let announcement_ids = |values: &[Value]| {
+        values
+            .iter()
+            .filter_map(|value| value.get("announcement_id").and_then(Value::as_str))
+            .map(str::to_string)
+            .collect::<BTreeSet<_>>()
+    };
+    for (idx, spend) in spends.iter().enumerate() {
+        let input = spend.coin_spend.coin.amount;
+        let output = spend
+            .evaluation
+            .additions
+            .iter()
+            .map(|coin| u128::from(coin.amount))
+            .sum::<u128>();
+        if output <= u128::from(input) {
+            continue;
+        }
+        let cat_wrapped = spend
+            .puzzle
+            .recognition
+            .wrappers
+            .iter()
+            .any(|wrapper| wrapper.name == "cat_layer");
+        let announced = announcement_ids(&spend.evaluation.announcements);
+        let asserted = announcement_ids(&spend.evaluation.assertions);
+        let linked = spends.iter().enumerate().any(|(other_idx, other)| {
+            other_idx != idx
+                && (announcement_ids(&other.evaluation.assertions)
+                    .intersection(&announced)
+                    .next()
+                    .is_some()
+                    || announcement_ids(&other.evaluation.announcements)
+                        .intersection(&asserted)
+                        .next()
+                        .is_some())
+        });
+        if cat_wrapped || linked {
+            continue;
+        }
+        warnings.push(WarningInfo {
+            code: "SPEND_OVERSPEND".to_string(),
+            message: format!(
+                "spend creates {output} mojos of coins from a {input} mojo coin and is not CAT-wrapped or announcement-linked to the spends that would fund it"
+            ),
+            spend_index: Some(idx),
+            details: Some(json!({
+                "coin_id": spend.coin_spend.coin.coin_id,
+                "input_amount": input,
+                "created_amount": u64::try_from(output).unwrap_or(u64::MAX),
+            })),
+            pointer: Some(format!("{}/evaluation/additions", spend_pointer(idx))),
+        });
+    }
+}
+
+/// Flags spends that dominate the bundle's cost (in bundles of two or more
+/// spends) or reach `threshold`, to point at the puzzle worth optimizing or
+/// a spend padding cost to grief the bundle.
+fn warn_high_cost_spends(
+    spends: &[SpendAnalysis],
+    threshold: Option<u64>,
+    warnings: &mut Vec<WarningInfo>,
+) {
+    let total = spends
+        .iter()
+        .map(|spend| spend.evaluation.cost)
+        .fold(0_u64, u64::saturating_add);
+    if total == 0 {
+        return;
+    }
+    for (idx, spend) in spends.iter().enumerate() {
+        let cost = spend.evaluation.cost;
+        let share_percent = u128::from(cost) * 100 / u128::from(total);
+        let dominant = spends.len() > 1 && share_percent >= u128::from(HIGH_COST_SHARE_PERCENT);
+        let over_threshold = threshold.is_some_and(|threshold| cost >= threshold);
+        if !dominant && !over_threshold {
+            continue;
+        }
+        warnings.push(WarningInfo {
+            code: "HIGH_COST_SPEND".to_string(),
+            message: format!("spend costs {cost}, {share_percent}% of the bundle's {total}"),
+            spend_index: Some(idx),
+            details: Some(json!({
+                "coin_id": spend.coin_spend.coin.coin_id,
+                "cost": cost,
+                "bundle_cost": total,
+                "share_percent": share_percent,
+            })),
+            pointer: Some(format!("{}/evaluation/cost", spend_pointer(idx))),
+        });
+    }
+}
+
+/// Flags AGG_SIG conditions whose key is none of the keys curried into the
+/// spend's recognized layers: a standard spend signed by anyone but its
+/// synthetic key is usually mis-constructed and fails at signing time.
+/// Spends with no key-bearing layer recognized are not judged.
+fn warn_foreign_signers(spends: &[SpendAnalysis], warnings: &mut Vec<WarningInfo>) {
+    for (idx, spend) in spends.iter().enumerate() {
+        let owners = recognized_keys(&spend.puzzle.recognition);
+        if owners.is_empty() {
+            continue;
+        }
+        for (condition_idx, condition) in spend.evaluation.conditions.iter().enumerate() {
+            if !condition.opcode.starts_with("AGG_SIG_") {
+                continue;
+            }
+            let Some(pubkey) = condition.args.first().and_then(Value::as_str) else {
+                continue;
+            };
+            if owners.contains(pubkey) {
+                continue;
+            }
+            warnings.push(WarningInfo {
+                code: "AGG_SIG_FOREIGN_KEY".to_string(),
+                message: format!(
+                    "{} requires a signature from {pubkey}, which is not a key of the spend's recognized puzzle",
+                    condition.opcode
+                ),
+                spend_index: Some(idx),
+                details: Some(json!({
+                    "opcode": condition.opcode,
+                    "pubkey": pubkey,
+                    "recognized_keys": owners,
+                })),
+                pointer: Some(format!("{}/evaluation/conditions/{condition_idx}", spend_pointer(idx))),
+            });
+        }
+    }
+}
+
+/// Compares the additions/removals a mempool item or block export reported
+/// with the ones computed here. Any difference means a bug or a
+/// consensus-version mismatch between this tool and the node, so each
+/// differing set gets one warning.
+fn warn_reported_coin_mismatch(
+    source: &InputSource,
+    removals: &[CoinRef],
+    additions: &[CoinRef],
+    warnings: &mut Vec<WarningInfo>,
+) {
+    let (InputSource::Mempool(Some(reported)) | InputSource::Block(Some(reported))) = source else {
+        return;
+    };
+    let ReportedCoins {
+        additions: reported_additions,
+        removals: reported_removals,
+    } = reported;
+    for (field, reported, computed) in [
+        ("additions", reported_additions, additions),
+        ("removals", reported_removals, removals),
+    ] {
+        let Some(reported) = reported else {
+            continue;
+        };
+        let reported = reported
+            .iter()
+            .map(|coin| encode_hex_prefixed(coin.coin_id().as_ref()))
+            .collect::<BTreeSet<_>>();
+        let computed = computed
+            .iter()
+            .map(|coin| coin.coin_id.clone())
+            .collect::<BTreeSet<_>>();
+        if reported == computed {
+            continue;
+        }
+        warnings.push(WarningInfo {
+            code: "RPC_COIN_MISMATCH".to_string(),
+            message: format!("the input's reported {field} differ from the {field} computed here"),
+            spend_index: None,
+            details: Some(json!({
+                "field": field,
+                "only_reported": reported.difference(&computed).collect::<Vec<_>>(),
+                "only_computed": computed.difference(&reported).collect::<Vec<_>>(),
+            })),
+            pointer: Some(format!("/result/summary/{field}")),
+        });
+    }
+}
+
+/// JSON pointer to `result.spends[idx]`.
+fn spend_pointer(idx: usize) -> String {
+    format!("/result/spends/{idx}")
+}
+
+/// Tightest bound for one timelock opcode; negative values never constrain.
+fn bound(conditions: &[RawCondition], opcode: u64, tighter: fn(u64, u64) -> u64) -> Option<u64> {
+    conditions
+        .iter()
+        .filter(|condition| condition.opcode == opcode)
+        .filter_map(|condition| condition.args.first().and_then(|arg| atom_to_u64(arg)))
+        .reduce(tighter)
+}
+
+fn push_contradiction(
+    window: &str,
+    after: Option<u64>,
+    before: Option<u64>,
+    spend_index: Option<usize>,
+    pointer: Option<String>,
+    warnings: &mut Vec<WarningInfo>,
+) {
+    let (Some(after), Some(before)) = (after, before) else {
+        return;
+    };
+    if after < before {
+        return;
+    }
+    warnings.push(WarningInfo {
+        code: "TIMELOCK_CONTRADICTION".to_string(),
+        message: format!(
+            "{window} window is empty: must be at least {after} and before {before}, so the spend can never be valid"
+        ),
+        spend_index,
+        details: Some(json!({
+            "window": window,
+            "not_before": after,
+            "before": before,
+        })),
+        pointer,
+    });
+}
+
+/// For singleton spends, tells a stateless re-creation (the odd child keeps
+/// the spent coin's puzzle hash, so the inner puzzle is unchanged) apart from
+/// a state change or a melt (no odd child).
+fn singleton_recreation(
+    coin: &Coin,
+    recognition: &PuzzleRecognition,
+    additions: &[CoinRef],
+) -> Option<Value> {
+    if recognition.wrappers.first()?.name != "singleton_layer" {
+        return None;
+    }
+    let spent_puzzle_hash = encode_hex_prefixed(coin.puzzle_hash.as_ref());
+    let Some(child) = additions.iter().find(|addition| addition.amount % 2 == 1) else {
+        return Some(json!({
+            "action": "singleton_melt",
+            "note": "no odd-amount output; the singleton is not recreated",
+        }));
+    };
+    let stateless = child.puzzle_hash == spent_puzzle_hash;
+    Some(json!({
+        "action": "singleton_recreate",
+        "kind": if stateless { "stateless" } else { "state_change" },
+        "child_coin_id": child.coin_id,
+        "note": if stateless {
+            "recreated with the same puzzle hash; the inner puzzle is unchanged and the lineage can repeat indefinitely"
+        } else {
+            "recreated with a different puzzle hash; the inner puzzle or its state changed"
+        },
+    }))
+}
+
+/// Adds a `singleton_launch` value-flow entry to every launcher spend. The
+/// launcher's coin id becomes the launcher id; the singleton's first inner
+/// puzzle hash is only known when the eve spend is in the same bundle.
+fn annotate_singleton_launches(spends: &mut [SpendAnalysis]) {
+    for idx in 0..spends.len() {
+        if let Some(note) = singleton_launch_note(&spends[idx], spends) {
+            spends[idx].puzzle_behavior.explanation.value_flow.push(note);
+        }
+    }
+}
+
+/// The `singleton_launch` entry for a launcher spend, looking for its eve
+/// spend among `spends`.
+fn singleton_launch_note(launcher: &SpendAnalysis, spends: &[SpendAnalysis]) -> Option<Value> {
+    if launcher.puzzle.recognition.wrappers.first()?.name != "singleton_launcher" {
+        return None;
+    }
+    let launcher_id = launcher.coin_spend.coin.coin_id.as_str();
+    let singleton = launcher.evaluation.additions.first();
+    let eve = spends.iter().find(|spend| {
+        spend.coin_spend.coin.parent_coin_id == launcher_id
+            && spend.puzzle.recognition.wrappers.first().is_some_and(|wrapper| {
+                wrapper.name == "singleton_layer"
+                    && wrapper.params.get("launcher_id").and_then(Value::as_str) == Some(launcher_id)
+            })
+    });
+    Some(json!({
+        "action": "singleton_launch",
+        "launcher_id": launcher_id,
+        "singleton_puzzle_hash": singleton.map(|coin| coin.puzzle_hash.clone()),
+        "amount": singleton.map(|coin| coin.amount),
+        "inner_puzzle_hash": eve
+            .and_then(|spend| spend.puzzle.recognition.wrappers.first())
+            .and_then(|wrapper| wrapper.inner_puzzle_tree_hash.clone()),
+        "note": if eve.is_some() {
+            "launches a new singleton; its eve spend is in this bundle"
+        } else {
+            "launches a new singleton; the eve spend is not in this bundle, so the inner puzzle hash is unknown"
+        },
+    }))
+}
+
+/// How a spend's `did_layer` solution was parsed: `spend` or `recover`.
+fn did_solution_kind(spend: &SpendAnalysis) -> Option<&str> {
+    spend
+        .puzzle
+        .recognition
+        .parsed_solution
+        .as_ref()?
+        .get("layers")?
+        .as_array()?
+        .iter()
+        .find(|layer| layer["layer"] == "did_layer")?
+        .get("result")?
+        .get("kind")?
+        .as_str()
+}
+
+/// Names the announcements DID spends exchange. A recovering DID asserts a
+/// coin announcement from each attesting DID in its recovery list, and each
+/// attesting spend announces `(recovering coin id, new inner puzzle hash,
+/// pubkey)`; both sides gain a `did_event` and point at each other. Other
+/// announcements from a DID spend are messages it signs for another spend,
+/// such as approving an NFT's transfer to it.
+fn annotate_did_announcements(spends: &mut [SpendAnalysis]) {
+    let kinds: Vec<Option<String>> =
+        spends.iter().map(|spend| did_solution_kind(spend).map(str::to_string)).collect();
+    let announced_by = |announcement_id: &Value, kind: &str| {
+        spends.iter().enumerate().find_map(|(idx, spend)| {
+            (kinds[idx].as_deref() == Some(kind)
+                && spend
+                    .evaluation
+                    .announcements
+                    .iter()
+                    .any(|announcement| &announcement["announcement_id"] == announcement_id))
+            .then_some(idx)
+        })
+    };
+    let asserted_by = |announcement_id: &Value, kind: &str| {
+        spends.iter().enumerate().find_map(|(idx, spend)| {
+            (kinds[idx].as_deref() == Some(kind)
+                && spend
+                    .evaluation
+                    .assertions
+                    .iter()
+                    .any(|assertion| &assertion["announcement_id"] == announcement_id))
+            .then_some(idx)
+        })
+    };
+    let mut events = Vec::new();
+    for (idx, spend) in spends.iter().enumerate() {
+        match kinds[idx].as_deref() {
+            Some("recover") => {
+                for (pos, assertion) in spend.evaluation.assertions.iter().enumerate() {
+                    if assertion["kind"] != "coin" {
+                        continue;
+                    }
+                    let attesting = announced_by(&assertion["announcement_id"], "spend");
+                    events.push((idx, false, pos, json!({
+                        "did_event": "recovery_attestation_assertion",
+                        "attesting_spend_index": attesting,
+                        "note": "the recovering DID requires this attestation from a DID in its recovery list",
+                    })));
+                }
+            }
+            Some("spend") => {
+                for (pos, announcement) in spend.evaluation.announcements.iter().enumerate() {
+                    let recovering = (announcement["kind"] == "coin")
+                        .then(|| asserted_by(&announcement["announcement_id"], "recover"))
+                        .flatten();
+                    let fields = match recovering {
+                        Some(recovering) => json!({
+                            "did_event": "recovery_attestation_announcement",
+                            "recovering_spend_index": recovering,
+                            "note": "attests to another DID's recovery to a new inner puzzle and key",
+                        }),
+                        None => json!({
+                            "did_event": "message_announcement",
+                            "note": "a message the DID signs for another spend to assert",
+                        }),
+                    };
+                    events.push((idx, true, pos, fields));
+                }
+            }
+            _ => {}
+        }
+    }
+    for (idx, is_announcement, pos, fields) in events {
+        let evaluation = &mut spends[idx].evaluation;
+        let target = if is_announcement {
+            &mut evaluation.announcements[pos]
+        } else {
+            &mut evaluation.assertions[pos]
+        };
+        if let (Value::Object(target), Value::Object(fields)) = (target, fields) {
+            target.extend(fields);
+        }
+    }
+}
+
+fn count_conditions(raw_conditions: &[RawCondition]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for condition in raw_conditions {
+        let name = condition_name(condition.opcode)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("UNKNOWN_{}", condition.opcode));
+        *counts.entry(name).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// A spend whose only CREATE_COIN recreates the spent coin's puzzle hash
+/// with the same amount, or less to pay a fee, is a no-op refresh, as in fee
+/// bumps and timelock resets. Returns the amount left to the fee.
+fn refresh_fee(coin: &Coin, create_coin: &[(Bytes32, u64, Option<Bytes>)]) -> Option<u64> {
+    let [(puzzle_hash, amount, _)] = create_coin else {
+        return None;
+    };
+    (*puzzle_hash == coin.puzzle_hash && *amount <= coin.amount).then(|| coin.amount - amount)
+}
+
+/// A spend that creates nothing and only announces, asserts or signs: the
+/// glue that ties offer and multi-party bundles together.
+fn is_glue_spend(raw_conditions: &[RawCondition]) -> bool {
+    let links = raw_conditions
+        .iter()
+        .any(|condition| (CREATE_COIN_ANNOUNCEMENT..=RECEIVE_MESSAGE).contains(&condition.opcode));
+    links
+        && raw_conditions
+            .iter()
+            .all(|condition| condition.opcode != CREATE_COIN && condition.opcode != RESERVE_FEE)
+}
+
+/// Assertions of an announcement the same spend makes are marked
+/// `self_assertion`: a coin asserting its own announcement is a common
+/// replay-protection idiom, not an assertion left for another spend to meet.
+fn collect_announcements(coin: &Coin, raw_conditions: &[RawCondition]) -> (Vec<Value>, Vec<Value>) {
+    let mut announcements = Vec::new();
+    let mut assertions = Vec::new();
+    for condition in raw_conditions {
+        let Some(arg) = condition.args.first() else {
+            continue;
+        };
+        match condition.opcode {
+            CREATE_COIN_ANNOUNCEMENT | CREATE_PUZZLE_ANNOUNCEMENT => {
+                let (kind, origin) = if condition.opcode == CREATE_COIN_ANNOUNCEMENT {
+                    ("coin", coin.coin_id())
+                } else {
+                    ("puzzle", coin.puzzle_hash)
+                };
+                announcements.push(json!({
+                    "kind": kind,
+                    "message": encode_hex_prefixed(arg),
+                    "announcement_id": encode_hex_prefixed(&announcement_id(origin.as_ref(), arg)),
+                }));
+            }
+            ASSERT_COIN_ANNOUNCEMENT | ASSERT_PUZZLE_ANNOUNCEMENT => {
+                assertions.push(json!({
+                    "kind": if condition.opcode == ASSERT_COIN_ANNOUNCEMENT { "coin" } else { "puzzle" },
+                    "announcement_id": encode_hex_prefixed(arg),
+                }));
+            }
+            _ => {}
+        }
+    }
+    for assertion in &mut assertions {
+        let own = announcements
+            .iter()
+            .any(|announcement| announcement["announcement_id"] == assertion["announcement_id"]);
+        assertion["self_assertion"] = json!(own);
+    }
+    (announcements, assertions)
+}
+
 fn add_optional_assertion(
     opcode: &str,
     value: Option<u64>,
     conditions: &mut Vec<ConditionInfo>,
     explanation: &mut Explanation,
+    timelocks: &TimelockContext,
 ) {
     if let Some(v) = value {
         conditions.push(ConditionInfo {
@@ -498,6 +1975,7 @@ fn add_optional_assertion(
         explanation.constraints.push(json!({
             "kind": opcode,
             "value": v,
+            "satisfied": timelocks.satisfied(opcode, v),
         }));
     }
 }
@@ -528,20 +2006,93 @@ fn coin_ref_from_coin(coin: &Coin) -> CoinRef {
     }
 }
 
-fn analyze_clvm_bytes(bytes: &[u8]) -> (String, StaticFeatures, bool) {
+fn canonicalize_clvm(spend: &CoinSpend) -> Option<CanonicalClvm> {
+    let puzzle = reserialize_without_backrefs(spend.puzzle_reveal.as_ref())?;
+    let solution = reserialize_without_backrefs(spend.solution.as_ref())?;
+    Some(CanonicalClvm {
+        puzzle_reveal_bytes: encode_hex_prefixed(&puzzle),
+        solution_bytes: encode_hex_prefixed(&solution),
+        serialized_len_bytes: puzzle.len(),
+    })
+}
+
+/// Sizes of a serialized tree with and without backrefs, independent of
+/// the encoding the input happened to use.
+fn encoded_lengths(bytes: &[u8]) -> Option<EncodedLengths> {
+    let mut allocator = ClvmAllocator::new();
+    let node = node_from_bytes_backrefs(&mut allocator, bytes).ok()?;
+    Some(EncodedLengths {
+        compact_len_bytes: node_to_bytes_backrefs(&allocator, node).ok()?.len(),
+        expanded_len_bytes: node_to_bytes(&allocator, node).ok()?.len(),
+    })
+}
+
+fn reserialize_without_backrefs(bytes: &[u8]) -> Option<Vec<u8>> {
     let mut allocator = ClvmAllocator::new();
+    let node = node_from_bytes_backrefs(&mut allocator, bytes).ok()?;
+    node_to_bytes(&allocator, node).ok()
+}
+
+struct ClvmAnalysis {
+    disasm: String,
+    features: StaticFeatures,
+    uses_backrefs: bool,
+    /// `None` when the bytes don't decode.
+    tree_hash: Option<TreeHash>,
+}
+
+thread_local! {
+    /// Reused across calls so bundles with many spends don't build a fresh
+    /// allocator per blob; each call rewinds it to a checkpoint when done.
+    static ANALYSIS_ALLOCATOR: RefCell<ClvmAllocator> = RefCell::new(ClvmAllocator::new());
+}
+
+/// Parses the blob once and derives disassembly, static features and the
+/// tree hash from the same node.
+fn analyze_clvm_bytes(
+    bytes: &[u8],
+    include_operator_counts: bool,
+    operator_version: usize,
+) -> ClvmAnalysis {
+    ANALYSIS_ALLOCATOR.with(|allocator| {
+        let mut allocator = allocator.borrow_mut();
+        let checkpoint = allocator.checkpoint();
+        let analysis = analyze_with_allocator(
+            &mut allocator,
+            bytes,
+            include_operator_counts,
+            operator_version,
+        );
+        allocator.restore_checkpoint(&checkpoint);
+        analysis
+    })
+}
+
+fn analyze_with_allocator(
+    allocator: &mut ClvmAllocator,
+    bytes: &[u8],
+    include_operator_counts: bool,
+    operator_version: usize,
+) -> ClvmAnalysis {
     let uses_backrefs = bytes.contains(&0xfe);
 
-    match node_from_bytes_backrefs(&mut allocator, bytes) {
-        Ok(node) => {
-            let disasm = disassemble(&allocator, node, Some(OPERATORS_LATEST_VERSION));
-            let features = extract_static_features(&allocator, node);
-            (disasm, features, uses_backrefs)
-        }
-        Err(err) => (
-            format!("<failed to disassemble: {err}>"),
-            StaticFeatures {
+    match node_from_bytes_backrefs(allocator, bytes) {
+        Ok(node) => ClvmAnalysis {
+            disasm: disassemble(allocator, node, Some(operator_version)),
+            features: extract_static_features(
+                allocator,
+                node,
+                include_operator_counts,
+                operator_version,
+            ),
+            uses_backrefs,
+            tree_hash: Some(tree_hash(allocator, node)),
+        },
+        Err(err) => ClvmAnalysis {
+            disasm: format!("<failed to disassemble: {err}>"),
+            features: StaticFeatures {
                 operators_used: Vec::new(),
+                operator_counts: None,
                 env_paths_used: Vec::new(),
                 constants: ConstantBuckets {
                     bytes32: Vec::new(),
@@ -550,13 +2101,19 @@ fn analyze_clvm_bytes(bytes: &[u8]) -> (String, StaticFeatures, bool) {
                 },
             },
             uses_backrefs,
-        ),
+            tree_hash: None,
+        },
     }
 }
 
-fn extract_static_features(allocator: &ClvmAllocator, root: NodePtr) -> StaticFeatures {
-    let keywords = keyword_from_atom(OPERATORS_LATEST_VERSION);
-    let mut operators = BTreeSet::<String>::new();
+fn extract_static_features(
+    allocator: &ClvmAllocator,
+    root: NodePtr,
+    include_operator_counts: bool,
+    operator_version: usize,
+) -> StaticFeatures {
+    let keywords = keyword_from_atom(operator_version);
+    let mut operators = BTreeMap::<String, u64>::new();
     let mut env_paths = BTreeSet::<u32>::new();
     let mut bytes32 = BTreeSet::<String>::new();
     let mut g1_pubkeys = BTreeSet::<String>::new();
@@ -576,7 +2133,8 @@ fn extract_static_features(allocator: &ClvmAllocator, root: NodePtr) -> StaticFe
     );
 
     StaticFeatures {
-        operators_used: operators.into_iter().collect(),
+        operators_used: operators.keys().cloned().collect(),
+        operator_counts: include_operator_counts.then_some(operators),
         env_paths_used: env_paths.into_iter().collect(),
         constants: ConstantBuckets {
             bytes32: bytes32.into_iter().collect(),
@@ -593,7 +2151,7 @@ fn visit_clvm(
     in_quoted: bool,
     operator_position: bool,
     keywords: &std::collections::HashMap<Vec<u8>, String>,
-    operators: &mut BTreeSet<String>,
+    operators: &mut BTreeMap<String, u64>,
     env_paths: &mut BTreeSet<u32>,
     bytes32: &mut BTreeSet<String>,
     g1_pubkeys: &mut BTreeSet<String>,
@@ -608,7 +2166,7 @@ fn visit_clvm(
             if !in_quoted && matches!(allocator.sexp(left), SExp::Atom) {
                 let atom = allocator.atom(left);
                 if let Some(name) = keywords.get(atom.as_ref()) {
-                    operators.insert(name.clone());
+                    *operators.entry(name.clone()).or_insert(0) += 1;
                 }
             }
 
@@ -679,7 +2237,337 @@ fn atom_to_u64(atom: &[u8]) -> Option<u64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chia_bls::{PublicKey, SecretKey};
     use chia_protocol::Program;
+    use chia_puzzle_types::standard::StandardSolution;
+    use chia_sdk_driver::{CatLayer, Layer, SpendContext, StandardLayer};
+    use clvm_traits::ToClvm;
+    use clvm_utils::tree_hash_from_bytes;
+
+    use crate::recognize::recognize_puzzle_structure;
+
+    /// A coin locked by puzzle `1`, so the solution is exactly the
+    /// `conditions` it emits.
+    fn identity_spend(parent: u8, amount: u64, conditions: impl ToClvm<ClvmAllocator>) -> CoinSpend {
+        let mut allocator = ClvmAllocator::new();
+        let solution = conditions.to_clvm(&mut allocator).expect("conditions");
+        let puzzle = Program::from(vec![0x01]);
+        let puzzle_hash = tree_hash_from_bytes(puzzle.as_ref()).expect("puzzle hash");
+        CoinSpend::new(
+            Coin::new(Bytes32::new([parent; 32]), puzzle_hash.into(), amount),
+            puzzle,
+            Program::from(node_to_bytes(&allocator, solution).expect("solution")),
+        )
+    }
+
+    fn create_coin(puzzle_hash: u8, amount: u64) -> (u64, (Bytes32, (u64, ()))) {
+        (CREATE_COIN, (Bytes32::new([puzzle_hash; 32]), (amount, ())))
+    }
+
+    #[test]
+    fn unlinked_overspend_warns_unless_cat_wrapped() {
+        let overspend = identity_spend(1, 1, vec![create_coin(0x22, 5)]);
+        let funding = identity_spend(2, 4, ());
+        let mut output = analyze_spends(&[overspend, funding], Network::Mainnet).expect("analyze");
+        let mut warnings = Vec::new();
+        warn_overspends(&output.result.spends, &mut warnings);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "SPEND_OVERSPEND");
+        assert_eq!(warnings[0].spend_index, Some(0));
+        assert_eq!(warnings[0].details.as_ref().expect("details")["created_amount"], 5);
+
+        let mut ctx = SpendContext::new();
+        let cat = CatLayer::new(Bytes32::new([7; 32]), StandardLayer::new(PublicKey::default()));
+        let cat_puzzle = cat.construct_puzzle(&mut ctx).expect("cat puzzle");
+        output.result.spends[0].puzzle.recognition =
+            recognize_puzzle_structure(&node_to_bytes(&ctx, cat_puzzle).expect("cat puzzle bytes"));
+        let mut warnings = Vec::new();
+        warn_overspends(&output.result.spends, &mut warnings);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn dominant_spend_warns_on_cost_share() {
+        let costly = identity_spend(1, 2, vec![create_coin(0x22, 1), create_coin(0x33, 1)]);
+        let cheap = identity_spend(2, 0, ());
+        let output = analyze_spends(&[costly.clone()], Network::Mainnet).expect("analyze");
+        let mut warnings = Vec::new();
+        warn_high_cost_spends(&output.result.spends, None, &mut warnings);
+        assert!(warnings.is_empty(), "a lone spend is all of its bundle's cost");
+
+        let output = analyze_spends(&[costly, cheap], Network::Mainnet).expect("analyze");
+        warn_high_cost_spends(&output.result.spends, None, &mut warnings);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "HIGH_COST_SPEND");
+        assert_eq!(warnings[0].spend_index, Some(0));
+        assert_eq!(warnings[0].pointer.as_deref(), Some("/result/spends/0/evaluation/cost"));
+    }
+
+    #[test]
+    fn empty_relative_window_warns() {
+        let condition = |opcode, value: u8| RawCondition {
+            opcode,
+            args: vec![vec![value]],
+        };
+        let mut warnings = Vec::new();
+        warn_timelock_contradictions(
+            &[vec![
+                condition(ASSERT_SECONDS_RELATIVE, 100),
+                condition(ASSERT_BEFORE_SECONDS_RELATIVE, 50),
+                condition(ASSERT_HEIGHT_RELATIVE, 1),
+                condition(ASSERT_BEFORE_HEIGHT_RELATIVE, 2),
+            ]],
+            &mut warnings,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "TIMELOCK_CONTRADICTION");
+        assert_eq!(warnings[0].spend_index, Some(0));
+    }
+
+    #[test]
+    fn validity_window_intersects_spends() {
+        let condition = |opcode, value: u8| RawCondition {
+            opcode,
+            args: vec![vec![value]],
+        };
+        let coin = |parent: u8| Coin::new(Bytes32::new([parent; 32]), Bytes32::default(), 1);
+        let spend = |parent| CoinSpend::new(coin(parent), Program::default(), Program::default());
+        let bundle = SpendBundle::new(vec![spend(1), spend(2)], Default::default());
+        let raw = [
+            vec![condition(ASSERT_HEIGHT_RELATIVE, 10)],
+            vec![condition(ASSERT_BEFORE_HEIGHT_ABSOLUTE, 105)],
+        ];
+        let mut warnings = Vec::new();
+
+        let unplaced = validity_window(&bundle, &raw, &BTreeMap::new(), &mut warnings).expect("window");
+        assert_eq!(unplaced.not_before_height, None);
+        assert_eq!(unplaced.before_height, Some(105));
+        assert_eq!(unplaced.unresolved_relative, vec![0]);
+        assert!(unplaced.satisfiable);
+
+        let confirmed = CoinConfirmation {
+            confirmed_height: Some(100),
+            confirmed_timestamp: None,
+        };
+        let context = BTreeMap::from([(coin(1).coin_id(), confirmed)]);
+        let window = validity_window(&bundle, &raw, &context, &mut warnings).expect("window");
+        assert_eq!(window.not_before_height, Some(110));
+        assert!(!window.satisfiable);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "VALIDITY_WINDOW_EMPTY");
+        assert!(validity_window(&bundle, &[Vec::new(), Vec::new()], &context, &mut warnings).is_none());
+    }
+
+    #[test]
+    fn signature_from_unrecognized_key_warns() {
+        let owner = SecretKey::from_seed(&[1; 32]).public_key();
+        let foreign = SecretKey::from_seed(&[2; 32]).public_key();
+        let agg_sig_me = vec![(AGG_SIG_ME, (foreign, (Bytes32::new([9; 32]), ())))];
+
+        let mut ctx = SpendContext::new();
+        let layer = StandardLayer::new(owner);
+        let puzzle = layer.construct_puzzle(&mut ctx).expect("puzzle");
+        let delegated_puzzle = ctx.alloc(&(1_u8, agg_sig_me.clone())).expect("delegated puzzle");
+        let solution = layer
+            .construct_solution(
+                &mut ctx,
+                StandardSolution {
+                    original_public_key: None,
+                    delegated_puzzle,
+                    solution: NodePtr::NIL,
+                },
+            )
+            .expect("solution");
+        let standard = CoinSpend::new(
+            Coin::new(Bytes32::new([1; 32]), Bytes32::new(tree_hash(&ctx, puzzle).to_bytes()), 1),
+            Program::from(node_to_bytes(&ctx, puzzle).expect("puzzle bytes")),
+            Program::from(node_to_bytes(&ctx, solution).expect("solution bytes")),
+        );
+        let output = analyze_spends(&[standard], Network::Mainnet).expect("analyze");
+        let foreign_key_warnings = |output: &InspectionOutput| {
+            output
+                .result
+                .warnings
+                .iter()
+                .filter(|warning| warning.code == "AGG_SIG_FOREIGN_KEY")
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let warnings = foreign_key_warnings(&output);
+        assert_eq!(warnings.len(), 1);
+        let foreign_hex = format!("0x{}", hex::encode(foreign.to_bytes()));
+        assert_eq!(warnings[0].details.as_ref().expect("details")["pubkey"], foreign_hex);
+        let document = serde_json::to_value(&output).expect("serialize");
+        let pointer = warnings[0].pointer.as_deref().expect("pointer");
+        assert_eq!(document.pointer(pointer).expect("resolves")["args"][0], foreign_hex);
+
+        // Without a key-bearing layer recognized, the spend isn't judged.
+        let output = analyze_spends(&[identity_spend(2, 0, agg_sig_me)], Network::Mainnet)
+            .expect("analyze");
+        assert!(foreign_key_warnings(&output).is_empty());
+    }
+
+    #[test]
+    fn own_announcement_assertion_is_self_assertion() {
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1);
+        let own_id = announcement_id(coin.coin_id().as_ref(), b"nonce").to_vec();
+        let raw = [
+            RawCondition { opcode: CREATE_COIN_ANNOUNCEMENT, args: vec![b"nonce".to_vec()] },
+            RawCondition { opcode: ASSERT_COIN_ANNOUNCEMENT, args: vec![own_id] },
+            RawCondition { opcode: ASSERT_PUZZLE_ANNOUNCEMENT, args: vec![vec![7; 32]] },
+        ];
+        let (announcements, assertions) = collect_announcements(&coin, &raw);
+        assert_eq!(announcements.len(), 1);
+        assert_eq!(assertions[0]["self_assertion"], true);
+        assert_eq!(assertions[1]["self_assertion"], false);
+    }
+
+    #[test]
+    fn did_recovery_announcements_are_paired() {
+        let announce = |messages: &[u8]| {
+            messages
+                .iter()
+                .map(|&message| (CREATE_COIN_ANNOUNCEMENT, (Bytes32::new([message; 32]), ())))
+                .collect::<Vec<_>>()
+        };
+        let assert_announced = |spend: &CoinSpend, message: u8| {
+            let id = announcement_id(spend.coin.coin_id().as_ref(), &[message; 32]);
+            (ASSERT_COIN_ANNOUNCEMENT, (Bytes32::new(id), ()))
+        };
+        let attesting = identity_spend(1, 0, announce(&[0xaa, 0xbb]));
+        let messenger = identity_spend(3, 0, announce(&[0xcc]));
+        let recovering = identity_spend(
+            2,
+            0,
+            vec![assert_announced(&attesting, 0xaa), assert_announced(&messenger, 0xcc)],
+        );
+        let mut spends = analyze_spends(&[attesting, recovering, messenger], Network::Mainnet)
+            .expect("analyze")
+            .result
+            .spends;
+        for (spend, kind) in spends.iter_mut().zip(["spend", "recover"]) {
+            spend.puzzle.recognition.parsed_solution = Some(json!({
+                "layers": [{ "layer": "did_layer", "result": { "status": "ok", "kind": kind } }],
+            }));
+        }
+        annotate_did_announcements(&mut spends);
+
+        let announcements = &spends[0].evaluation.announcements;
+        assert_eq!(announcements[0]["did_event"], "recovery_attestation_announcement");
+        assert_eq!(announcements[0]["recovering_spend_index"], 1);
+        assert_eq!(announcements[1]["did_event"], "message_announcement");
+        let assertions = &spends[1].evaluation.assertions;
+        assert_eq!(assertions[0]["attesting_spend_index"], 0);
+        assert_eq!(assertions[1]["did_event"], "recovery_attestation_assertion");
+        assert_eq!(assertions[1]["attesting_spend_index"], Value::Null);
+    }
+
+    #[test]
+    fn absolute_timelocks_are_judged_without_confirmation() {
+        let spend = identity_spend(
+            1,
+            0,
+            vec![(ASSERT_HEIGHT_ABSOLUTE, (100u64, ())), (ASSERT_HEIGHT_RELATIVE, (5u64, ()))],
+        );
+        let satisfied = |current_height: Option<u32>| {
+            let output = inspect_bundle_with_config(
+                InputSource::Mempool(None),
+                SpendBundle::new(vec![spend.clone()], Signature::default()),
+                Vec::new(),
+                &InspectConfig {
+                    current_height,
+                    ..Default::default()
+                },
+            )
+            .expect("inspect");
+            let constraints = &output.result.spends[0].puzzle_behavior.explanation.constraints;
+            let judged = |kind: &str| {
+                constraints
+                    .iter()
+                    .find(|constraint| constraint["kind"] == kind)
+                    .map(|constraint| constraint["satisfied"].clone())
+                    .expect("constraint")
+            };
+            (judged("ASSERT_HEIGHT_ABSOLUTE"), judged("ASSERT_HEIGHT_RELATIVE"))
+        };
+        assert_eq!(satisfied(Some(150)), (json!(true), Value::Null));
+        assert_eq!(satisfied(Some(50)), (json!(false), Value::Null));
+        assert_eq!(satisfied(None), (Value::Null, Value::Null));
+    }
+
+    #[test]
+    fn reserve_fees_are_reconciled_bundle_wide() {
+        let reserve = |value: u8| RawCondition {
+            opcode: RESERVE_FEE,
+            args: vec![vec![value]],
+        };
+        let raw = [vec![reserve(30)], Vec::new(), vec![reserve(20)]];
+        let mut warnings = Vec::new();
+        let reservation = reconcile_reserve_fee(&raw, 50, &mut warnings).expect("reservation");
+        assert_eq!(reservation.reserved_total, 50);
+        assert_eq!(reservation.spend_indices, vec![0, 2]);
+        assert!(warnings.is_empty());
+
+        reconcile_reserve_fee(&raw, 40, &mut warnings);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, "RESERVE_FEE_EXCEEDS_FEE");
+        assert!(reconcile_reserve_fee(&raw[1..2], 0, &mut warnings).is_none());
+    }
+
+    #[test]
+    fn value_balance_is_created_minus_consumed() {
+        assert_eq!(value_balance(10, [3, 4]), -3);
+        assert_eq!(value_balance(1, [u64::MAX]), i128::from(u64::MAX) - 1);
+        assert_eq!(value_balance(0, []), 0);
+    }
+
+    #[test]
+    fn glue_spend_only_links() {
+        let condition = |opcode| RawCondition {
+            opcode,
+            args: vec![vec![0xab]],
+        };
+        assert!(is_glue_spend(&[condition(ASSERT_PUZZLE_ANNOUNCEMENT), condition(AGG_SIG_UNSAFE)]));
+        assert!(!is_glue_spend(&[condition(ASSERT_PUZZLE_ANNOUNCEMENT), condition(CREATE_COIN)]));
+        assert!(!is_glue_spend(&[condition(AGG_SIG_UNSAFE)]));
+    }
+
+    #[test]
+    fn agg_sig_conditions_require_signature() {
+        let condition = |opcode| RawCondition {
+            opcode,
+            args: vec![vec![0xab]],
+        };
+        assert!(requires_signature(&[condition(CREATE_COIN), condition(AGG_SIG_PARENT)]));
+        assert!(requires_signature(&[condition(AGG_SIG_ME)]));
+        assert!(!requires_signature(&[condition(CREATE_COIN_ANNOUNCEMENT), condition(CREATE_COIN)]));
+    }
+
+    #[test]
+    fn refresh_recreates_the_spent_coin() {
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 100);
+        assert_eq!(refresh_fee(&coin, &[(coin.puzzle_hash, 100, None)]), Some(0));
+        assert_eq!(refresh_fee(&coin, &[(coin.puzzle_hash, 90, None)]), Some(10));
+        assert_eq!(refresh_fee(&coin, &[(Bytes32::new([3; 32]), 100, None)]), None);
+        assert_eq!(
+            refresh_fee(&coin, &[(coin.puzzle_hash, 50, None), (coin.puzzle_hash, 50, None)]),
+            None
+        );
+    }
+
+    #[test]
+    fn amount_order_puts_largest_first() {
+        let coin = |id: &str, amount| CoinRef {
+            coin_id: id.to_string(),
+            parent_coin_id: String::new(),
+            puzzle_hash: String::new(),
+            amount,
+        };
+        let mut coins = vec![coin("0x01", 5), coin("0x03", 10), coin("0x02", 10)];
+        CoinOrder::AmountDesc.sort(&mut coins);
+        let ids = coins.iter().map(|c| c.coin_id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["0x02", "0x03", "0x01"]);
+    }
 
     #[test]
     fn atom_u64_parser() {
@@ -692,7 +2580,58 @@ mod tests {
     #[test]
     fn analyze_clvm_smoke() {
         let program = Program::from(vec![0xff, 0x01, 0x01]);
-        let (_disasm, features, _backrefs) = analyze_clvm_bytes(program.as_ref());
+        let features =
+            analyze_clvm_bytes(program.as_ref(), false, OPERATORS_LATEST_VERSION).features;
         assert!(features.operators_used.iter().any(|op| op == "q"));
+        assert!(features.operator_counts.is_none());
+    }
+
+    #[test]
+    fn pooled_allocator_gives_repeatable_analysis() {
+        let program = Program::from(hex::decode("ff10ffff0101ffff010280").expect("program hex"));
+        let first = analyze_clvm_bytes(program.as_ref(), false, OPERATORS_LATEST_VERSION);
+        let second = analyze_clvm_bytes(program.as_ref(), false, OPERATORS_LATEST_VERSION);
+        assert_eq!(first.disasm, second.disasm);
+        assert_eq!(first.tree_hash, second.tree_hash);
+        assert!(
+            analyze_clvm_bytes(&[0xff], false, OPERATORS_LATEST_VERSION)
+                .tree_hash
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn operator_histogram_counts_repeats() {
+        // (+ (q . 1) (q . 2))
+        let program = Program::from(hex::decode("ff10ffff0101ffff010280").expect("program hex"));
+        let features =
+            analyze_clvm_bytes(program.as_ref(), true, OPERATORS_LATEST_VERSION).features;
+        let counts = features.operator_counts.expect("operator counts");
+        assert_eq!(counts.get("q"), Some(&2));
+        assert_eq!(counts.get("+"), Some(&1));
+    }
+
+    #[test]
+    fn canonicalize_expands_backrefs() {
+        let mut allocator = ClvmAllocator::new();
+        let atom = allocator.new_atom(&[0x42; 32]).expect("atom");
+        let pair = allocator.new_pair(atom, atom).expect("pair");
+        let plain = node_to_bytes(&allocator, pair).expect("plain bytes");
+        let compressed = node_to_bytes_backrefs(&allocator, pair).expect("backref bytes");
+        assert_ne!(plain, compressed);
+        assert_eq!(reserialize_without_backrefs(&compressed), Some(plain));
+    }
+
+    #[test]
+    fn encoded_lengths_ignore_input_encoding() {
+        let mut allocator = ClvmAllocator::new();
+        let atom = allocator.new_atom(&[0x42; 32]).expect("atom");
+        let pair = allocator.new_pair(atom, atom).expect("pair");
+        let plain = node_to_bytes(&allocator, pair).expect("plain bytes");
+        let compressed = node_to_bytes_backrefs(&allocator, pair).expect("backref bytes");
+        let lengths = encoded_lengths(&plain).expect("lengths");
+        assert_eq!(encoded_lengths(&compressed), Some(lengths));
+        assert_eq!(lengths.expanded_len_bytes, plain.len());
+        assert_eq!(lengths.compact_len_bytes, compressed.len());
     }
 }
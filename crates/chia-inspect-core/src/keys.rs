@@ -0,0 +1,77 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::schema::{InspectionOutput, PuzzleRecognition};
+
+/// Recognition params that hold a BLS public key.
+const KEY_PARAMS: &[&str] = &["public_key", "synthetic_key"];
+
+/// Every BLS public key the bundle references: AGG_SIG condition keys of any
+/// kind plus keys curried into recognized layers (standard synthetic keys,
+/// p2_delegated_puzzle and p2_delegated_conditions keys).
+pub fn collect_public_keys(output: &InspectionOutput) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    let signatures = &output.result.signatures;
+    for agg_sig in signatures.agg_sig_me.iter().chain(&signatures.agg_sig_unsafe) {
+        keys.insert(agg_sig.pubkey.clone());
+    }
+    for spend in &output.result.spends {
+        for enforced in &spend.puzzle_behavior.explanation.enforced_signatures {
+            if let Some(pubkey) = enforced.get("pubkey").and_then(Value::as_str) {
+                keys.insert(pubkey.to_string());
+            }
+        }
+        for wrapper in &spend.puzzle.recognition.wrappers {
+            collect_param_keys(&wrapper.params, &mut keys);
+        }
+    }
+    keys
+}
+
+/// The keys curried into a spend's recognized layers: the keys its puzzle
+/// can be expected to require signatures from.
+pub fn recognized_keys(recognition: &PuzzleRecognition) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    for wrapper in &recognition.wrappers {
+        collect_param_keys(&wrapper.params, &mut keys);
+    }
+    keys
+}
+
+fn collect_param_keys(value: &Value, keys: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map {
+                match field.as_str() {
+                    Some(key) if KEY_PARAMS.contains(&name.as_str()) => {
+                        keys.insert(key.to_string());
+                    }
+                    _ => collect_param_keys(field, keys),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_param_keys(item, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn collects_nested_layer_keys() {
+        let mut keys = BTreeSet::new();
+        collect_param_keys(
+            &json!({ "synthetic_key": "0xaa", "inner": [{ "public_key": "0xbb" }], "other": "0xcc" }),
+            &mut keys,
+        );
+        assert_eq!(keys.into_iter().collect::<Vec<_>>(), vec!["0xaa", "0xbb"]);
+    }
+}
@@ -1,11 +1,34 @@
+pub mod assets;
+pub mod cache;
+pub mod conditions;
+pub mod coverage;
+pub mod display;
 pub mod error;
+pub mod events;
+pub mod graph;
 pub mod inspect;
 pub mod input;
+pub mod keys;
+pub mod markdown;
+pub mod network;
+pub mod offer;
+pub mod readiness;
 pub mod recognize;
+pub mod registry;
 pub mod schema;
+pub mod snapshot;
+mod stream;
+pub mod text;
 pub mod util;
 
-pub use inspect::{ExplainLevel, inspect_bundle};
+pub use cache::Recognizer;
+pub use inspect::{
+    CoinOrder, ExplainLevel, InspectConfig, ProgressCallback, analyze_spends, inspect_bundle,
+    inspect_bundle_streaming, inspect_bundle_with_config, spend_bundle_id,
+};
 pub use input::{
-    InputSource, load_block_spends_input, load_coin_spend_input, load_mempool_blob_input,
+    CoinConfirmation, InputSource, LoadedInput, MempoolDumpItem, ReportedCoins,
+    load_autodetected_input, load_block_spends_input, load_coin_parts_input, load_coin_spend_input, load_generator_input,
+    load_mempool_blob_input, load_mempool_dump_input, load_spent_context,
+    normalized_spend_bundle_json, resolve_generator_refs,
 };
@@ -0,0 +1,120 @@
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::schema::{InspectionOutput, SpendAnalysis};
+
+/// Renders an inspection document as a GitHub-flavored markdown report:
+/// a bundle summary, a value-flow table of net deltas by puzzle hash, a
+/// warnings callout, and one section per spend with its recognized layer
+/// stack and conditions.
+pub fn render_markdown(output: &InspectionOutput) -> String {
+    let result = &output.result;
+    let summary = &result.summary;
+    let mut md = String::from("# Spend bundle inspection\n\n");
+    let _ = writeln!(md, "| | |\n|---|---|");
+    let _ = writeln!(md, "| Bundle id | `{}` |", result.bundle_id);
+    let _ = writeln!(md, "| Status | {} |", result.status);
+    let _ = writeln!(md, "| Submittable | {} |", yes_no(result.readiness.submittable));
+    let _ = writeln!(md, "| Spends | {} |", result.spends.len());
+    let _ = writeln!(
+        md,
+        "| Removals / additions | {} / {} |",
+        summary.removals.len(),
+        summary.additions.len()
+    );
+    let _ = writeln!(md, "| Fee | {} mojos |", summary.fee_mojos);
+    if let Some(error) = &result.error {
+        let _ = writeln!(md, "| Error | {}: {} |", error.kind, cell(&error.message));
+    }
+
+    md.push_str("\n## Value flow\n\n");
+    if summary.net_xch_delta_by_puzzle_hash.is_empty() {
+        md.push_str("No net XCH movement.\n");
+    } else {
+        md.push_str("| Puzzle hash | Net delta (mojos) |\n|---|---:|\n");
+        for delta in &summary.net_xch_delta_by_puzzle_hash {
+            let _ = writeln!(md, "| `{}` | {:+} |", delta.puzzle_hash, delta.delta_mojos);
+        }
+    }
+
+    if !result.warnings.is_empty() || !result.readiness.blockers.is_empty() {
+        md.push_str("\n> [!WARNING]\n");
+        for warning in &result.warnings {
+            let spend = warning
+                .spend_index
+                .map_or_else(String::new, |idx| format!(" (spend #{idx})"));
+            let _ = writeln!(md, "> - **{}**{spend}: {}", warning.code, warning.message);
+        }
+        for blocker in &result.readiness.blockers {
+            let _ = writeln!(md, "> - **blocker `{}`**: {}", blocker.kind, blocker.message);
+        }
+    }
+
+    for (idx, spend) in result.spends.iter().enumerate() {
+        render_spend(&mut md, idx, spend);
+    }
+    md
+}
+
+fn render_spend(md: &mut String, idx: usize, spend: &SpendAnalysis) {
+    let coin = &spend.coin_spend.coin;
+    let _ = writeln!(md, "\n## Spend #{idx}\n");
+    let _ = writeln!(md, "- Coin: `{}` ({} mojos)", coin.coin_id, coin.amount);
+    let _ = writeln!(md, "- Puzzle hash: `{}`", coin.puzzle_hash);
+    let evaluation = &spend.evaluation;
+    let _ = writeln!(md, "- Evaluation: {} (cost {})", evaluation.status, evaluation.cost);
+    if let Some(failure) = &evaluation.failure {
+        let _ = writeln!(md, "- Failure: {}: {}", failure.kind, failure.message);
+    }
+    if let Some(role) = &spend.puzzle_behavior.explanation.role {
+        let _ = writeln!(md, "- Role: {role}");
+    }
+
+    md.push_str("\n**Layers**\n\n");
+    if spend.puzzle.recognition.wrappers.is_empty() {
+        md.push_str("- unrecognized\n");
+    }
+    for (depth, wrapper) in spend.puzzle.recognition.wrappers.iter().enumerate() {
+        let _ = write!(md, "{}1. `{}`", "   ".repeat(depth), wrapper.name);
+        if let Value::Object(params) = &wrapper.params {
+            let params = params
+                .iter()
+                .map(|(key, value)| format!("{key}: `{}`", compact(value)))
+                .collect::<Vec<_>>();
+            if !params.is_empty() {
+                let _ = write!(md, " — {}", params.join(", "));
+            }
+        }
+        md.push('\n');
+    }
+
+    md.push_str("\n**Conditions**\n\n");
+    if evaluation.conditions.is_empty() {
+        md.push_str("- none\n");
+    }
+    for condition in &evaluation.conditions {
+        let args = condition
+            .args
+            .iter()
+            .map(|arg| format!("`{}`", compact(arg)))
+            .collect::<Vec<_>>();
+        let _ = writeln!(md, "- {} {}", condition.opcode, args.join(" "));
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+/// Keeps free text from breaking out of a table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn compact(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result, bail};
+use chia_consensus::consensus_constants::{ConsensusConstants, TEST_CONSTANTS};
+use chia_protocol::Bytes32;
+use chia_sha2::Sha256;
+use serde_json::Value;
+
+use crate::util::decode_hex;
+
+/// Networks whose genesis challenge a bundle can be checked against. AGG_SIG
+/// messages other than `AGG_SIG_UNSAFE` commit to the genesis challenge, so
+/// a signature only verifies on the network it was made for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet11,
+}
+
+impl Network {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet11 => "testnet11",
+        }
+    }
+
+    pub fn genesis_challenge(self) -> Bytes32 {
+        let hex = match self {
+            Self::Mainnet => "ccd5bb71183532bff220ba46c268991a3ff07eb358e8255a65c30a2dce0e5fbb",
+            Self::Testnet11 => "37a90eb5185a9c4439a91ddc98bbadce7b4feba060d50116a067de66bf236615",
+        };
+        let bytes: [u8; 32] = hex::decode(hex)
+            .expect("genesis challenge hex")
+            .try_into()
+            .expect("32-byte genesis challenge");
+        Bytes32::new(bytes)
+    }
+
+    /// The offline constants with this network's genesis challenge and the
+    /// AGG_SIG additional data derived from it.
+    pub fn constants(self) -> ConsensusConstants {
+        with_genesis_challenge(TEST_CONSTANTS.clone(), self.genesis_challenge())
+    }
+}
+
+fn with_genesis_challenge(mut constants: ConsensusConstants, genesis: Bytes32) -> ConsensusConstants {
+    let derive = |opcode: u8| {
+        let mut hasher = Sha256::new();
+        hasher.update(genesis);
+        hasher.update([opcode]);
+        Bytes32::new(hasher.finalize())
+    };
+    constants.genesis_challenge = genesis;
+    constants.agg_sig_me_additional_data = genesis;
+    constants.agg_sig_parent_additional_data = derive(43);
+    constants.agg_sig_puzzle_additional_data = derive(44);
+    constants.agg_sig_amount_additional_data = derive(45);
+    constants.agg_sig_puzzle_amount_additional_data = derive(46);
+    constants.agg_sig_parent_amount_additional_data = derive(47);
+    constants.agg_sig_parent_puzzle_additional_data = derive(48);
+    constants
+}
+
+/// Reads consensus constants for a simnet or private network from a JSON
+/// object, over the offline (mainnet) defaults. Keys match the consensus
+/// crate's field names case-insensitively, so both `genesis_challenge` and
+/// chia's `GENESIS_CHALLENGE` config style work. A new genesis challenge
+/// re-derives the AGG_SIG additional data unless those are given too.
+///
+/// Only the constants offline inspection depends on are read; the names of
+/// any other keys are returned so the caller can say they were ignored.
+pub fn constants_from_json(json: &str) -> Result<(ConsensusConstants, Vec<String>)> {
+    let value: Value = serde_json::from_str(json).context("constants must be JSON")?;
+    let Some(fields) = value.as_object() else {
+        bail!("constants must be a JSON object");
+    };
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    };
+
+    let mut constants = TEST_CONSTANTS.clone();
+    if let Some(value) = field("genesis_challenge") {
+        constants = with_genesis_challenge(constants, bytes32_constant("genesis_challenge", value)?);
+    }
+    let hashes: [(&str, &mut Bytes32); 7] = [
+        ("agg_sig_me_additional_data", &mut constants.agg_sig_me_additional_data),
+        ("agg_sig_parent_additional_data", &mut constants.agg_sig_parent_additional_data),
+        ("agg_sig_puzzle_additional_data", &mut constants.agg_sig_puzzle_additional_data),
+        ("agg_sig_amount_additional_data", &mut constants.agg_sig_amount_additional_data),
+        ("agg_sig_puzzle_amount_additional_data", &mut constants.agg_sig_puzzle_amount_additional_data),
+        ("agg_sig_parent_amount_additional_data", &mut constants.agg_sig_parent_amount_additional_data),
+        ("agg_sig_parent_puzzle_additional_data", &mut constants.agg_sig_parent_puzzle_additional_data),
+    ];
+    for (name, slot) in hashes {
+        if let Some(value) = field(name) {
+            *slot = bytes32_constant(name, value)?;
+        }
+    }
+    let amounts: [(&str, &mut u64); 3] = [
+        ("max_block_cost_clvm", &mut constants.max_block_cost_clvm),
+        ("cost_per_byte", &mut constants.cost_per_byte),
+        ("max_coin_amount", &mut constants.max_coin_amount),
+    ];
+    for (name, slot) in amounts {
+        if let Some(value) = field(name) {
+            *slot = u64_constant(name, value)?;
+        }
+    }
+
+    let ignored = fields
+        .keys()
+        .filter(|key| !CONSTANT_FIELDS.iter().any(|name| key.eq_ignore_ascii_case(name)))
+        .cloned()
+        .collect();
+    Ok((constants, ignored))
+}
+
+/// The constants [`constants_from_json`] reads.
+const CONSTANT_FIELDS: &[&str] = &[
+    "genesis_challenge",
+    "agg_sig_me_additional_data",
+    "agg_sig_parent_additional_data",
+    "agg_sig_puzzle_additional_data",
+    "agg_sig_amount_additional_data",
+    "agg_sig_puzzle_amount_additional_data",
+    "agg_sig_parent_amount_additional_data",
+    "agg_sig_parent_puzzle_additional_data",
+    "max_block_cost_clvm",
+    "cost_per_byte",
+    "max_coin_amount",
+];
+
+fn bytes32_constant(name: &str, value: &Value) -> Result<Bytes32> {
+    let bytes = value
+        .as_str()
+        .map(decode_hex)
+        .with_context(|| format!("{name} must be a hex string"))??;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow::anyhow!("{name} must be 32 bytes, got {}", bytes.len()))?;
+    Ok(Bytes32::new(bytes))
+}
+
+fn u64_constant(name: &str, value: &Value) -> Result<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+    .with_context(|| format!("{name} must be a non-negative integer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mainnet_matches_offline_constants() {
+        let mainnet = Network::Mainnet.constants();
+        assert_eq!(mainnet.genesis_challenge, TEST_CONSTANTS.genesis_challenge);
+        assert_eq!(
+            mainnet.agg_sig_parent_additional_data,
+            TEST_CONSTANTS.agg_sig_parent_additional_data
+        );
+        assert_ne!(
+            Network::Testnet11.constants().agg_sig_me_additional_data,
+            mainnet.agg_sig_me_additional_data
+        );
+    }
+
+    #[test]
+    fn custom_constants_override_defaults() {
+        let genesis = "37a90eb5185a9c4439a91ddc98bbadce7b4feba060d50116a067de66bf236615";
+        let json = format!(
+            r#"{{"GENESIS_CHALLENGE": "0x{genesis}", "cost_per_byte": "7", "MIN_PLOT_SIZE": 32}}"#
+        );
+        let (constants, ignored) = constants_from_json(&json).expect("constants");
+        let testnet = Network::Testnet11.constants();
+        assert_eq!(constants.genesis_challenge, testnet.genesis_challenge);
+        assert_eq!(
+            constants.agg_sig_parent_puzzle_additional_data,
+            testnet.agg_sig_parent_puzzle_additional_data
+        );
+        assert_eq!(constants.cost_per_byte, 7);
+        assert_eq!(ignored, vec!["MIN_PLOT_SIZE"]);
+        assert!(constants_from_json(r#"{"genesis_challenge": "0x01"}"#).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Value, json};
+
+use crate::schema::SpendAnalysis;
+use crate::util::{decode_hex, printable_memo_text};
+
+const SETTLEMENT_LAYER: &str = "settlement_layer";
+const CAT_LAYER: &str = "cat_layer";
+
+/// Checks whether a bundle containing settlement spends is offer-shaped.
+///
+/// Returns `None` when no spend settles through the settlement puzzle. The
+/// notarized payments are read from each settlement spend's puzzle
+/// announcements, so the check also works on structure-only (cached)
+/// recognition.
+pub fn check_offer_shape(spends: &[SpendAnalysis]) -> Option<Value> {
+    let settlement_indices = spends
+        .iter()
+        .enumerate()
+        .filter(|(_, spend)| {
+            spend
+                .puzzle
+                .recognition
+                .wrappers
+                .last()
+                .is_some_and(|wrapper| wrapper.name == SETTLEMENT_LAYER)
+        })
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    if settlement_indices.is_empty() {
+        return None;
+    }
+
+    let created = spends
+        .iter()
+        .flat_map(|spend| &spend.evaluation.additions)
+        .map(|coin| coin.coin_id.as_str())
+        .collect::<BTreeSet<_>>();
+    let announced = spends
+        .iter()
+        .flat_map(|spend| &spend.evaluation.announcements)
+        .filter_map(announcement_id)
+        .collect::<BTreeSet<_>>();
+    let asserted = spends
+        .iter()
+        .flat_map(|spend| &spend.evaluation.assertions)
+        .filter_map(announcement_id)
+        .collect::<BTreeSet<_>>();
+
+    let mut issues = Vec::new();
+    let mut notarized_payments = 0_usize;
+    for &idx in &settlement_indices {
+        let spend = &spends[idx];
+        if let Some(err) = spend
+            .puzzle
+            .recognition
+            .wrappers
+            .last()
+            .and_then(|wrapper| wrapper.parse_error.as_ref())
+        {
+            issues.push(issue("settlement_solution_unparsed", err.clone(), Some(idx)));
+        }
+        if !created.contains(spend.coin_spend.coin.coin_id.as_str()) {
+            issues.push(issue(
+                "settlement_coin_not_ephemeral",
+                "settlement coin is not created within the bundle".to_string(),
+                Some(idx),
+            ));
+        }
+
+        let payments = spend
+            .evaluation
+            .announcements
+            .iter()
+            .filter(|announcement| announcement.get("kind").and_then(Value::as_str) == Some("puzzle"))
+            .filter_map(announcement_id)
+            .collect::<Vec<_>>();
+        if payments.is_empty() {
+            issues.push(issue(
+                "no_notarized_payments",
+                "settlement spend announces no notarized payments".to_string(),
+                Some(idx),
+            ));
+        }
+        notarized_payments += payments.len();
+        for id in payments {
+            if !asserted.contains(id) {
+                issues.push(issue(
+                    "unrequested_payment",
+                    format!("notarized payment {id} is not asserted by any spend"),
+                    Some(idx),
+                ));
+            }
+        }
+    }
+
+    for (idx, spend) in spends.iter().enumerate() {
+        for assertion in &spend.evaluation.assertions {
+            let Some(id) = announcement_id(assertion) else {
+                continue;
+            };
+            if !announced.contains(id) {
+                issues.push(issue(
+                    "unmatched_requested_payment",
+                    format!("asserted announcement {id} is not made by any spend"),
+                    Some(idx),
+                ));
+            }
+        }
+    }
+
+    let known_puzzle_hashes = puzzle_hash_spends(spends);
+    let requested_payments = settlement_indices
+        .iter()
+        .flat_map(|&idx| requested_payments(idx, &spends[idx], &known_puzzle_hashes))
+        .collect::<Vec<_>>();
+
+    Some(json!({
+        "kind": "offer",
+        "clean": issues.is_empty(),
+        "settlement_spends": settlement_indices,
+        "notarized_payments": notarized_payments,
+        "nonce_groups": nonce_groups(&requested_payments),
+        "requested_payments": requested_payments,
+        "issues": issues,
+    }))
+}
+
+/// Groups settlement spends by the notarized-payment nonces they share. A
+/// nonce commits to the offered side, so spends under one nonce are the two
+/// halves of the same trade.
+fn nonce_groups(requested_payments: &[Value]) -> Vec<Value> {
+    let mut groups = BTreeMap::<&str, BTreeSet<u64>>::new();
+    for payment in requested_payments {
+        let (Some(nonce), Some(idx)) = (
+            payment.get("nonce").and_then(Value::as_str),
+            payment.get("spend_index").and_then(Value::as_u64),
+        ) else {
+            continue;
+        };
+        groups.entry(nonce).or_default().insert(idx);
+    }
+    groups
+        .into_iter()
+        .map(|(nonce, spend_indices)| json!({ "nonce": nonce, "spend_indices": spend_indices }))
+        .collect()
+}
+
+/// Maps every puzzle hash a spend's coin or created coins use to the
+/// indices of those spends.
+fn puzzle_hash_spends(spends: &[SpendAnalysis]) -> BTreeMap<&str, BTreeSet<usize>> {
+    let mut index = BTreeMap::<&str, BTreeSet<usize>>::new();
+    for (idx, spend) in spends.iter().enumerate() {
+        let coins = std::iter::once(&spend.coin_spend.coin).chain(&spend.evaluation.additions);
+        for coin in coins {
+            index.entry(coin.puzzle_hash.as_str()).or_default().insert(idx);
+        }
+    }
+    index
+}
+
+/// Decodes one payment memo: 32-byte memos naming a puzzle hash used in the
+/// bundle gain the `linked_spend_indices` that spend or create coins with
+/// it, and other printable memos gain `text`.
+fn decode_memo(memo: &Value, known_puzzle_hashes: &BTreeMap<&str, BTreeSet<usize>>) -> Value {
+    let Some(hex) = memo.as_str() else {
+        return memo.clone();
+    };
+    let mut decoded = json!({ "hex": hex });
+    let bytes = decode_hex(hex).unwrap_or_default();
+    if let Some(spend_indices) = known_puzzle_hashes.get(hex).filter(|_| bytes.len() == 32) {
+        decoded["linked_spend_indices"] = json!(spend_indices);
+    } else if let Some(text) = printable_memo_text(&bytes) {
+        decoded["text"] = json!(text);
+    }
+    decoded
+}
+
+/// Lists the payments a settlement spend makes, tagged with the asset being
+/// paid: the CAT asset id when a CAT layer wraps the settlement puzzle, `null`
+/// for XCH. Needs the parsed settlement solution, so structure-only
+/// recognition yields nothing here.
+fn requested_payments(
+    spend_index: usize,
+    spend: &SpendAnalysis,
+    known_puzzle_hashes: &BTreeMap<&str, BTreeSet<usize>>,
+) -> Vec<Value> {
+    let recognition = &spend.puzzle.recognition;
+    let asset_id = recognition
+        .wrappers
+        .iter()
+        .find(|wrapper| wrapper.name == CAT_LAYER)
+        .and_then(|wrapper| wrapper.params.get("asset_id").cloned())
+        .unwrap_or(Value::Null);
+    let Some(layers) = recognition
+        .parsed_solution
+        .as_ref()
+        .and_then(|parsed| parsed.get("layers"))
+        .and_then(Value::as_array)
+    else {
+        return Vec::new();
+    };
+    let Some(settlement) = layers
+        .iter()
+        .find(|layer| layer.get("layer").and_then(Value::as_str) == Some(SETTLEMENT_LAYER))
+    else {
+        return Vec::new();
+    };
+
+    let mut out = Vec::new();
+    let notarized = settlement
+        .pointer("/result/notarized_payments")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for notarized_payment in notarized {
+        let payments = notarized_payment
+            .get("payments")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for payment in payments {
+            out.push(json!({
+                "spend_index": spend_index,
+                "asset_id": asset_id,
+                "amount": payment.get("amount"),
+                "to_puzzle_hash": payment.get("puzzle_hash"),
+                "nonce": notarized_payment.get("nonce"),
+                "memos": payment
+                    .get("memos")
+                    .and_then(Value::as_array)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|memo| decode_memo(memo, known_puzzle_hashes))
+                    .collect::<Vec<_>>(),
+            }));
+        }
+    }
+    out
+}
+
+fn announcement_id(value: &Value) -> Option<&str> {
+    value.get("announcement_id").and_then(Value::as_str)
+}
+
+fn issue(code: &str, message: String, spend_index: Option<usize>) -> Value {
+    json!({
+        "code": code,
+        "message": message,
+        "spend_index": spend_index,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend, Program};
+    use chia_puzzle_types::Memos;
+    use chia_puzzle_types::offer::{NotarizedPayment, Payment, SettlementPaymentsSolution};
+    use chia_sdk_driver::{Layer, SettlementLayer, SpendContext};
+    use clvm_utils::tree_hash;
+    use clvmr::serde::node_to_bytes;
+
+    use crate::analyze_spends;
+    use crate::network::Network;
+    use crate::schema::InspectionOutput;
+
+    /// Inspects one settlement coin of `amount`, spent to make
+    /// `notarized_payments`.
+    fn inspect_settlement(
+        ctx: &mut SpendContext,
+        amount: u64,
+        notarized_payments: Vec<NotarizedPayment>,
+    ) -> InspectionOutput {
+        let puzzle = SettlementLayer.construct_puzzle(ctx).expect("puzzle");
+        let solution = SettlementLayer
+            .construct_solution(ctx, SettlementPaymentsSolution { notarized_payments })
+            .expect("solution");
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new(tree_hash(ctx, puzzle).to_bytes()), amount);
+        let spend = CoinSpend::new(
+            coin,
+            Program::from(node_to_bytes(ctx, puzzle).expect("puzzle bytes")),
+            Program::from(node_to_bytes(ctx, solution).expect("solution bytes")),
+        );
+        analyze_spends(&[spend], Network::Mainnet).expect("analyze")
+    }
+
+    #[test]
+    fn payments_group_by_shared_nonce() {
+        let payment = |idx: usize, nonce: &str| json!({ "spend_index": idx, "nonce": nonce });
+        let groups = nonce_groups(&[
+            payment(0, "0xaa"),
+            payment(2, "0xaa"),
+            payment(1, "0xbb"),
+            payment(2, "0xaa"),
+        ]);
+        assert_eq!(
+            groups,
+            vec![
+                json!({ "nonce": "0xaa", "spend_indices": [0, 2] }),
+                json!({ "nonce": "0xbb", "spend_indices": [1] }),
+            ]
+        );
+    }
+
+    #[test]
+    fn memos_decode_text_and_link_known_puzzle_hashes() {
+        let mut ctx = SpendContext::new();
+        let puzzle_hash = Bytes32::new([0x44; 32]);
+        let memos = ctx
+            .alloc(&vec![Bytes::new(puzzle_hash.to_vec()), Bytes::new(b"hi".to_vec())])
+            .expect("memos");
+        let output = inspect_settlement(
+            &mut ctx,
+            1,
+            vec![NotarizedPayment {
+                nonce: Bytes32::new([3; 32]),
+                payments: vec![Payment::new(puzzle_hash, 1, Memos::Some(memos))],
+            }],
+        );
+        let offer = check_offer_shape(&output.result.spends).expect("offer check");
+        let created = format!("0x{}", "44".repeat(32));
+        assert_eq!(
+            offer["requested_payments"][0]["memos"],
+            json!([
+                { "hex": created, "linked_spend_indices": [0] },
+                { "hex": "0x6869", "text": "hi" },
+            ])
+        );
+    }
+
+    #[test]
+    fn settlement_spend_without_payments_is_not_clean() {
+        let mut ctx = SpendContext::new();
+        let output = inspect_settlement(&mut ctx, 0, Vec::new());
+        let offer = check_offer_shape(&output.result.spends).expect("offer check");
+        assert_eq!(offer["clean"], json!(false));
+        let codes = offer["issues"]
+            .as_array()
+            .expect("issues")
+            .iter()
+            .map(|issue| issue["code"].as_str().expect("code"))
+            .collect::<Vec<_>>();
+        assert!(codes.contains(&"no_notarized_payments"));
+        assert!(codes.contains(&"settlement_coin_not_ephemeral"));
+
+        let mut unsettled = output.result.spends;
+        unsettled[0].puzzle.recognition.wrappers.clear();
+        assert!(check_offer_shape(&unsettled).is_none());
+    }
+}
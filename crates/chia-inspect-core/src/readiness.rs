@@ -0,0 +1,321 @@
+use std::collections::BTreeSet;
+
+use chia_bls::{PublicKey, Signature, aggregate_verify};
+use chia_consensus::consensus_constants::ConsensusConstants;
+use chia_consensus::owned_conditions::OwnedSpendBundleConditions;
+use chia_protocol::{Bytes, SpendBundle};
+
+use crate::inspect::InspectConfig;
+use crate::network::Network;
+use crate::schema::{CostSummary, InspectionOutput, Readiness, ReadinessBlocker, Verdict};
+use crate::util::encode_hex_prefixed;
+
+/// Aggregates the submit-time checks into a single verdict.
+///
+/// Announcement assertions, duplicate spends and the reserve fee are also
+/// enforced by consensus evaluation, so a bundle that failed evaluation is
+/// reported through [`failed_readiness`] instead.
+pub fn assess_readiness(
+    spend_bundle: &SpendBundle,
+    owned: &OwnedSpendBundleConditions,
+    constants: &ConsensusConstants,
+    config: &InspectConfig,
+) -> Readiness {
+    let mut blockers = Vec::new();
+
+    if !signature_verifies(spend_bundle, owned, constants) {
+        let message = if spend_bundle.aggregated_signature == Signature::default() {
+            "the bundle is unsigned (default aggregated_signature) but has AGG_SIG conditions"
+        } else {
+            "aggregated_signature does not verify against the bundle's AGG_SIG conditions"
+        };
+        blockers.push(blocker("invalid_signature", message.to_string()));
+    }
+
+    if owned.removal_amount < owned.addition_amount {
+        blockers.push(blocker(
+            "negative_fee",
+            format!(
+                "additions ({}) exceed removals ({})",
+                owned.addition_amount, owned.removal_amount
+            ),
+        ));
+    } else if owned.removal_amount - owned.addition_amount < u128::from(owned.reserve_fee) {
+        blockers.push(blocker(
+            "reserve_fee_unsatisfied",
+            format!(
+                "RESERVE_FEE of {} exceeds the implied fee of {}",
+                owned.reserve_fee,
+                owned.removal_amount - owned.addition_amount
+            ),
+        ));
+    }
+
+    let mut seen = BTreeSet::new();
+    for spend in &spend_bundle.coin_spends {
+        let coin_id = spend.coin.coin_id();
+        if !seen.insert(coin_id) {
+            blockers.push(blocker(
+                "double_spend",
+                format!("coin {} is spent more than once", encode_hex_prefixed(coin_id.as_ref())),
+            ));
+        }
+    }
+
+    check_absolute_timelocks(owned, config, &mut blockers);
+
+    Readiness {
+        submittable: blockers.is_empty(),
+        blockers,
+        min_fee_mojos: None,
+        networks_accepted: None,
+    }
+}
+
+pub fn failed_readiness(message: &str) -> Readiness {
+    Readiness {
+        submittable: false,
+        blockers: vec![blocker("validation_error", message.to_string())],
+        min_fee_mojos: None,
+        networks_accepted: None,
+    }
+}
+
+/// Minimum fee for mempool acceptance at `fee_per_cost` mojos per unit of
+/// cost, from the bundle's total cost (CLVM plus serialized size).
+pub fn estimate_min_fee(output: &InspectionOutput, fee_per_cost: u64) -> u64 {
+    output.result.summary.cost.total_cost.saturating_mul(fee_per_cost)
+}
+
+/// Records the minimum fee on the readiness report and blocks the bundle
+/// when its fee falls short.
+pub fn check_min_fee(output: &mut InspectionOutput, fee_per_cost: u64) {
+    let min_fee = estimate_min_fee(output, fee_per_cost);
+    let fee = output.result.summary.fee_mojos;
+    let readiness = &mut output.result.readiness;
+    readiness.min_fee_mojos = Some(min_fee);
+    if fee < min_fee {
+        readiness.submittable = false;
+        readiness.blockers.push(blocker(
+            "fee_below_minimum",
+            format!("fee of {fee} is below the {min_fee} mojos required at {fee_per_cost} per cost"),
+        ));
+    }
+}
+
+/// Reduces a finished inspection to its verdict: status, readiness and fee.
+pub fn verdict(output: &InspectionOutput) -> Verdict {
+    let result = &output.result;
+    Verdict {
+        bundle_id: result.bundle_id.clone(),
+        status: result.status.clone(),
+        submittable: result.readiness.submittable,
+        fee_mojos: result.summary.fee_mojos,
+        blockers: result.readiness.blockers.clone(),
+    }
+}
+
+pub fn cost_summary(
+    serialized_size_bytes: u64,
+    clvm_cost: u64,
+    constants: &ConsensusConstants,
+) -> CostSummary {
+    let byte_cost = serialized_size_bytes.saturating_mul(constants.cost_per_byte);
+    CostSummary {
+        serialized_size_bytes,
+        clvm_cost,
+        byte_cost,
+        total_cost: clvm_cost.saturating_add(byte_cost),
+    }
+}
+
+fn check_absolute_timelocks(
+    owned: &OwnedSpendBundleConditions,
+    config: &InspectConfig,
+    blockers: &mut Vec<ReadinessBlocker>,
+) {
+    if let Some(before) = owned.before_height_absolute {
+        if owned.height_absolute >= before {
+            blockers.push(blocker(
+                "timelock_unsatisfiable",
+                format!(
+                    "ASSERT_HEIGHT_ABSOLUTE {} is not below ASSERT_BEFORE_HEIGHT_ABSOLUTE {before}",
+                    owned.height_absolute
+                ),
+            ));
+        }
+    }
+    if let Some(before) = owned.before_seconds_absolute {
+        if owned.seconds_absolute >= before {
+            blockers.push(blocker(
+                "timelock_unsatisfiable",
+                format!(
+                    "ASSERT_SECONDS_ABSOLUTE {} is not below ASSERT_BEFORE_SECONDS_ABSOLUTE {before}",
+                    owned.seconds_absolute
+                ),
+            ));
+        }
+    }
+
+    if let Some(height) = config.current_height {
+        if owned.height_absolute > height {
+            blockers.push(blocker(
+                "timelock_not_reached",
+                format!(
+                    "ASSERT_HEIGHT_ABSOLUTE {} is above current height {height}",
+                    owned.height_absolute
+                ),
+            ));
+        }
+        if owned.before_height_absolute.is_some_and(|before| before <= height) {
+            blockers.push(blocker(
+                "timelock_expired",
+                format!("ASSERT_BEFORE_HEIGHT_ABSOLUTE has passed at current height {height}"),
+            ));
+        }
+    }
+    if let Some(timestamp) = config.current_timestamp {
+        if owned.seconds_absolute > timestamp {
+            blockers.push(blocker(
+                "timelock_not_reached",
+                format!(
+                    "ASSERT_SECONDS_ABSOLUTE {} is after current timestamp {timestamp}",
+                    owned.seconds_absolute
+                ),
+            ));
+        }
+        if owned
+            .before_seconds_absolute
+            .is_some_and(|before| before <= timestamp)
+        {
+            blockers.push(blocker(
+                "timelock_expired",
+                format!("ASSERT_BEFORE_SECONDS_ABSOLUTE has passed at current timestamp {timestamp}"),
+            ));
+        }
+    }
+}
+
+/// Names the networks, out of `candidates`, whose AGG_SIG additional data
+/// makes the aggregated signature verify. Conditions do not depend on the
+/// network, so this is the only part of validation that can differ.
+pub fn accepted_networks(
+    spend_bundle: &SpendBundle,
+    owned: &OwnedSpendBundleConditions,
+    candidates: &[Network],
+) -> Vec<String> {
+    candidates
+        .iter()
+        .filter(|network| signature_verifies(spend_bundle, owned, &network.constants()))
+        .map(|network| network.name().to_string())
+        .collect()
+}
+
+fn signature_verifies(
+    spend_bundle: &SpendBundle,
+    owned: &OwnedSpendBundleConditions,
+    constants: &ConsensusConstants,
+) -> bool {
+    let mut pairs = Vec::<(PublicKey, Vec<u8>)>::new();
+    for spend in &owned.spends {
+        let amount = clvm_amount_bytes(spend.coin_amount);
+        push_signed_pairs(
+            &mut pairs,
+            &spend.agg_sig_me,
+            &[spend.coin_id.as_ref(), constants.agg_sig_me_additional_data.as_ref()],
+        );
+        push_signed_pairs(
+            &mut pairs,
+            &spend.agg_sig_parent,
+            &[spend.parent_id.as_ref(), constants.agg_sig_parent_additional_data.as_ref()],
+        );
+        push_signed_pairs(
+            &mut pairs,
+            &spend.agg_sig_puzzle,
+            &[spend.puzzle_hash.as_ref(), constants.agg_sig_puzzle_additional_data.as_ref()],
+        );
+        push_signed_pairs(
+            &mut pairs,
+            &spend.agg_sig_amount,
+            &[&amount, constants.agg_sig_amount_additional_data.as_ref()],
+        );
+        push_signed_pairs(
+            &mut pairs,
+            &spend.agg_sig_puzzle_amount,
+            &[
+                spend.puzzle_hash.as_ref(),
+                &amount,
+                constants.agg_sig_puzzle_amount_additional_data.as_ref(),
+            ],
+        );
+        push_signed_pairs(
+            &mut pairs,
+            &spend.agg_sig_parent_amount,
+            &[
+                spend.parent_id.as_ref(),
+                &amount,
+                constants.agg_sig_parent_amount_additional_data.as_ref(),
+            ],
+        );
+        push_signed_pairs(
+            &mut pairs,
+            &spend.agg_sig_parent_puzzle,
+            &[
+                spend.parent_id.as_ref(),
+                spend.puzzle_hash.as_ref(),
+                constants.agg_sig_parent_puzzle_additional_data.as_ref(),
+            ],
+        );
+    }
+    push_signed_pairs(&mut pairs, &owned.agg_sig_unsafe, &[]);
+
+    aggregate_verify(&spend_bundle.aggregated_signature, pairs)
+}
+
+fn push_signed_pairs(
+    out: &mut Vec<(PublicKey, Vec<u8>)>,
+    pairs: &[(PublicKey, Bytes)],
+    suffix: &[&[u8]],
+) {
+    for (pk, msg) in pairs {
+        let mut final_msg = msg.to_vec();
+        for part in suffix {
+            final_msg.extend_from_slice(part);
+        }
+        out.push((*pk, final_msg));
+    }
+}
+
+/// Minimal CLVM integer encoding, matching how consensus appends the coin
+/// amount to AGG_SIG_*_AMOUNT messages.
+fn clvm_amount_bytes(amount: u64) -> Vec<u8> {
+    let mut bytes: Vec<u8> = amount
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|b| *b == 0)
+        .collect();
+    if bytes.first().is_some_and(|b| b & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    bytes
+}
+
+fn blocker(kind: &str, message: String) -> ReadinessBlocker {
+    ReadinessBlocker {
+        kind: kind.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clvm_amount_encoding() {
+        assert_eq!(clvm_amount_bytes(0), Vec::<u8>::new());
+        assert_eq!(clvm_amount_bytes(1), vec![0x01]);
+        assert_eq!(clvm_amount_bytes(0x80), vec![0x00, 0x80]);
+        assert_eq!(clvm_amount_bytes(0x0100), vec![0x01, 0x00]);
+    }
+}
@@ -1,28 +1,55 @@
 use chialisp::classic::clvm::OPERATORS_LATEST_VERSION;
 use chialisp::classic::clvm_tools::binutils::disassemble;
-use chia_puzzle_types::did::DidSolution;
+use chia_bls::PublicKey;
+use chia_puzzle_types::did::{DidArgs, DidSolution};
+use chia_puzzle_types::singleton::{LauncherSolution, SingletonStruct};
+use chia_puzzles::{
+    DELEGATED_TAIL_HASH, EVERYTHING_WITH_SIGNATURE_HASH, GENESIS_BY_COIN_ID_HASH,
+    GENESIS_BY_PUZZLE_HASH_HASH, P2_DELEGATED_PUZZLE_HASH, SINGLETON_LAUNCHER_HASH,
+};
 use chia_sdk_driver::{
     AugmentedConditionLayer, BulletinLayer, CatLayer, DidLayer, Layer, NftOwnershipLayer,
     NftStateLayer, OptionContractLayer, P2CurriedLayer, P2DelegatedConditionsLayer,
     P2OneOfManyLayer, P2SingletonLayer, Puzzle as DriverPuzzle, RevocationLayer,
     RoyaltyTransferLayer, SettlementLayer, SingletonLayer, StandardLayer, StreamLayer,
 };
-use clvm_utils::tree_hash;
-use clvmr::allocator::NodePtr;
-use clvmr::serde::node_from_bytes_backrefs;
-use clvmr::Allocator;
+use clvm_traits::FromClvm;
+use clvm_utils::{TreeHash, tree_hash};
+use clvmr::allocator::{NodePtr, SExp};
+use clvmr::serde::{node_from_bytes_backrefs, node_to_bytes};
+use clvmr::{Allocator, ChiaDialect, run_program};
 use serde_json::{Value, json};
 
-use crate::schema::{PuzzleCandidate, PuzzleRecognition, WrapperInfo};
-use crate::util::encode_hex_prefixed;
+use crate::conditions::condition_info_from_node;
+use crate::error::InspectError;
+use crate::inspect::DEFAULT_MAX_COST;
+use crate::schema::{
+    CurriedPuzzleInfo, PuzzleCandidate, PuzzleRecognition, WrapperEvidence, WrapperInfo,
+};
+use crate::util::{encode_hex_prefixed, printable_memo_text};
 
-const SOURCE_REPO: &str = "xch-dev/chia-wallet-sdk";
-const SOURCE_REF: &str = "0.33.0";
+/// Repo and ref of the chia-wallet-sdk this build links, read from the
+/// lockfile by the build script; stamped into every SDK layer's `WrapperInfo`.
+pub const SOURCE_REPO: &str = env!("CHIA_SDK_SOURCE_REPO");
+pub const SOURCE_REF: &str = env!("CHIA_SDK_SOURCE_REF");
+const CHIA_PUZZLES_REPO: &str = "Chia-Network/chia_puzzles";
+const CHIA_PUZZLES_REF: &str = "0.20.3";
 const MAX_LAYER_DEPTH: usize = 32;
 
+/// Mod hashes of the standard CAT TAILs, keyed by the `tail_kind` reported
+/// in `cat_layer` params.
+const KNOWN_TAILS: [(&str, [u8; 32]); 4] = [
+    ("genesis_by_coin_id", GENESIS_BY_COIN_ID_HASH),
+    ("genesis_by_puzzle_hash", GENESIS_BY_PUZZLE_HASH_HASH),
+    ("everything_with_signature", EVERYTHING_WITH_SIGNATURE_HASH),
+    ("delegated_tail", DELEGATED_TAIL_HASH),
+];
+
 #[derive(Debug, Clone)]
 struct LayerMatch {
     name: &'static str,
+    source_repo: &'static str,
+    source_ref: &'static str,
     source_path: &'static str,
     params: Value,
     next_puzzle: Option<DriverPuzzle>,
@@ -31,29 +58,130 @@ struct LayerMatch {
     parse_error: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, FromClvm)]
+#[clvm(curry)]
+struct AugmentedConditionArgs {
+    condition: NodePtr,
+    _inner_puzzle: NodePtr,
+}
+
+#[derive(Debug, Clone, Copy, FromClvm)]
+#[clvm(curry)]
+struct P2DelegatedPuzzleArgs {
+    public_key: PublicKey,
+}
+
+#[derive(Debug, Clone, Copy, FromClvm)]
+#[clvm(list)]
+struct P2DelegatedPuzzleSolution {
+    delegated_puzzle: NodePtr,
+    delegated_solution: NodePtr,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecognizeOptions {
+    /// Never look at the solution, even when one is given; recognition is
+    /// structure-only, as with [`recognize_puzzle_structure`].
+    pub skip_solutions: bool,
+    /// Drop the unstructured `parsed_debug` dumps from parsed layer
+    /// solutions, keeping only the structured fields.
+    pub compact: bool,
+}
+
+pub fn recognize_with_options(
+    puzzle_reveal_bytes: &[u8],
+    solution_bytes: &[u8],
+    options: &RecognizeOptions,
+) -> PuzzleRecognition {
+    if options.skip_solutions {
+        return recognize_puzzle_structure(puzzle_reveal_bytes);
+    }
+    let mut recognition = recognize_puzzle_and_solution(puzzle_reveal_bytes, solution_bytes);
+    if options.compact {
+        strip_parsed_debug(&mut recognition);
+    }
+    recognition
+}
+
+/// Removes every layer's `parsed_debug` dump from `parsed_solution`.
+pub fn strip_parsed_debug(recognition: &mut PuzzleRecognition) {
+    let Some(layers) = recognition
+        .parsed_solution
+        .as_mut()
+        .and_then(|parsed| parsed.get_mut("layers"))
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+    for result in layers.iter_mut().filter_map(|layer| layer.get_mut("result")) {
+        if let Some(result) = result.as_object_mut() {
+            result.remove("parsed_debug");
+        }
+    }
+}
+
 pub fn recognize_puzzle_and_solution(
     puzzle_reveal_bytes: &[u8],
     solution_bytes: &[u8],
 ) -> PuzzleRecognition {
+    recognize_layers(puzzle_reveal_bytes, Some(solution_bytes))
+}
+
+/// Strict variant of [`recognize_puzzle_and_solution`]: a puzzle reveal that
+/// can't be decoded at all is an `Err` instead of a `decode_error` embedded in
+/// the recognition. Solution decode failures are still reported in-band.
+pub fn try_recognize_puzzle_and_solution(
+    puzzle_reveal_bytes: &[u8],
+    solution_bytes: &[u8],
+) -> Result<PuzzleRecognition, InspectError> {
+    try_recognize_layers(puzzle_reveal_bytes, Some(solution_bytes), None)
+}
+
+/// [`recognize_puzzle_and_solution`] for a puzzle whose outer layers are
+/// already known (`layers`, outer to inner, e.g. from an earlier recognition
+/// of the same puzzle hash): only the named detector runs at each of those
+/// depths, and every detector runs below them.
+pub(crate) fn recognize_known_layers(
+    puzzle_reveal_bytes: &[u8],
+    solution_bytes: &[u8],
+    layers: &[String],
+) -> PuzzleRecognition {
+    try_recognize_layers(puzzle_reveal_bytes, Some(solution_bytes), Some(layers))
+        .unwrap_or_else(undecodable_recognition)
+}
+
+fn recognize_layers(puzzle_reveal_bytes: &[u8], solution_bytes: Option<&[u8]>) -> PuzzleRecognition {
+    try_recognize_layers(puzzle_reveal_bytes, solution_bytes, None)
+        .unwrap_or_else(undecodable_recognition)
+}
+
+fn undecodable_recognition(err: InspectError) -> PuzzleRecognition {
+    PuzzleRecognition {
+        recognized: false,
+        candidates: Vec::new(),
+        wrappers: Vec::new(),
+        parsed_solution: Some(json!({
+            "layers": [],
+            "decode_error": err.to_string(),
+        })),
+        inner_most_layer: None,
+        unrecognized_curried: None,
+    }
+}
+
+fn try_recognize_layers(
+    puzzle_reveal_bytes: &[u8],
+    solution_bytes: Option<&[u8]>,
+    known_layers: Option<&[String]>,
+) -> Result<PuzzleRecognition, InspectError> {
     let mut allocator = Allocator::new();
 
-    let puzzle_ptr = match node_from_bytes_backrefs(&mut allocator, puzzle_reveal_bytes) {
-        Ok(ptr) => ptr,
-        Err(err) => {
-            return PuzzleRecognition {
-                recognized: false,
-                candidates: Vec::new(),
-                wrappers: Vec::new(),
-                parsed_solution: Some(json!({
-                    "layers": [],
-                    "decode_error": format!("failed to decode puzzle_reveal bytes: {err}"),
-                })),
-            };
-        }
-    };
+    let puzzle_ptr = node_from_bytes_backrefs(&mut allocator, puzzle_reveal_bytes)
+        .map_err(|err| InspectError::PuzzleDecode(err.to_string()))?;
 
-    let solution_ptr = node_from_bytes_backrefs(&mut allocator, solution_bytes).ok();
-    let solution_decode_error = if solution_ptr.is_none() {
+    let solution_ptr =
+        solution_bytes.and_then(|bytes| node_from_bytes_backrefs(&mut allocator, bytes).ok());
+    let solution_decode_error = if solution_bytes.is_some() && solution_ptr.is_none() {
         Some("failed to decode solution bytes".to_string())
     } else {
         None
@@ -64,10 +192,15 @@ pub fn recognize_puzzle_and_solution(
     let mut wrappers = Vec::<WrapperInfo>::new();
     let mut candidates = Vec::<PuzzleCandidate>::new();
     let mut solution_layers = Vec::<Value>::new();
+    let mut unrecognized_curried = None;
 
-    for _ in 0..MAX_LAYER_DEPTH {
-        let matches = collect_matches(&allocator, current_puzzle, current_solution);
+    for depth in 0..MAX_LAYER_DEPTH {
+        let only = known_layers
+            .and_then(|layers| layers.get(depth))
+            .map(String::as_str);
+        let matches = collect_matches(&allocator, current_puzzle, current_solution, only);
         if matches.is_empty() {
+            unrecognized_curried = curried_puzzle_info(&allocator, current_puzzle);
             break;
         }
 
@@ -87,8 +220,8 @@ pub fn recognize_puzzle_and_solution(
 
         wrappers.push(WrapperInfo {
             name: matched.name.to_string(),
-            source_repo: SOURCE_REPO.to_string(),
-            source_ref: SOURCE_REF.to_string(),
+            source_repo: matched.source_repo.to_string(),
+            source_ref: matched.source_ref.to_string(),
             source_path: Some(matched.source_path.to_string()),
             mod_hash: encode_tree_hash(current_puzzle.mod_hash().as_ref()),
             curried_args_tree_hash: current_puzzle
@@ -99,6 +232,7 @@ pub fn recognize_puzzle_and_solution(
                 .map(|p| encode_tree_hash(p.curried_puzzle_hash().as_ref())),
             params: matched.params.clone(),
             parse_error: matched.parse_error.clone(),
+            evidence: Some(wrapper_evidence(&allocator, current_puzzle, &matched)),
         });
 
         candidates.push(candidate_from_match(
@@ -139,74 +273,115 @@ pub fn recognize_puzzle_and_solution(
         }))
     };
 
-    PuzzleRecognition {
+    Ok(PuzzleRecognition {
         recognized: !wrappers.is_empty(),
         candidates,
+        inner_most_layer: wrappers.last().map(|wrapper| wrapper.name.clone()),
         wrappers,
         parsed_solution,
-    }
+        unrecognized_curried,
+    })
+}
+
+/// Fallback for a puzzle no detector matched: if it is curried, its mod
+/// hash and curried arguments still say a lot about it.
+fn curried_puzzle_info(allocator: &Allocator, puzzle: DriverPuzzle) -> Option<CurriedPuzzleInfo> {
+    let curried = puzzle.as_curried()?;
+    let args = curried_args(allocator, curried.args)?;
+    Some(CurriedPuzzleInfo {
+        puzzle_hash: encode_tree_hash(puzzle.curried_puzzle_hash().as_ref()),
+        mod_hash: encode_tree_hash(curried.mod_hash.as_ref()),
+        arg_count: args.len(),
+        arg_tree_hashes: args
+            .into_iter()
+            .map(|arg| encode_tree_hash(tree_hash(allocator, arg).as_ref()))
+            .collect(),
+    })
+}
+
+/// Whether any parsed layer solution took an escape path instead of the
+/// committed inner puzzle: the revocation layer's hidden path, a puzzle
+/// revealed from a p2_one_of_many merkle tree, or a standard spend through
+/// its hidden puzzle (`original_public_key` set). Needs parsed solutions.
+pub fn used_escape_path(recognition: &PuzzleRecognition) -> bool {
+    let Some(layers) = recognition
+        .parsed_solution
+        .as_ref()
+        .and_then(|parsed| parsed.get("layers"))
+        .and_then(Value::as_array)
+    else {
+        return false;
+    };
+    layers.iter().any(|layer| {
+        let result = layer.get("result");
+        let flag = |key: &str| result.and_then(|r| r.get(key)) == Some(&Value::Bool(true));
+        match layer.get("layer").and_then(Value::as_str) {
+            Some("revocation_layer") => flag("hidden"),
+            Some("standard_layer") => flag("has_original_public_key"),
+            Some("p2_one_of_many_layer") => {
+                result.and_then(|r| r.get("status")).and_then(Value::as_str) == Some("ok")
+            }
+            _ => false,
+        }
+    })
 }
 
+/// Recognizes the layer stack from the puzzle reveal alone. Wrappers and
+/// candidates depend only on the puzzle, so this is what gets cached.
+pub fn recognize_puzzle_structure(puzzle_reveal_bytes: &[u8]) -> PuzzleRecognition {
+    let mut recognition = recognize_layers(puzzle_reveal_bytes, None);
+    recognition.parsed_solution = None;
+    recognition
+}
+
+/// A layer detector: matches the puzzle's structure and, given a solution,
+/// parses it for the layer.
+type Detector = fn(&Allocator, DriverPuzzle, Option<NodePtr>) -> Option<LayerMatch>;
+
+/// Every detector, keyed by the layer name it reports, in match order.
+const DETECTORS: &[(&str, Detector)] = &[
+    ("cat_layer", try_cat_layer),
+    ("singleton_layer", try_singleton_layer),
+    ("singleton_launcher", try_singleton_launcher),
+    ("did_layer", try_did_layer),
+    ("nft_state_layer", try_nft_state_layer),
+    ("nft_ownership_layer", try_nft_ownership_layer),
+    ("royalty_transfer_layer", |allocator, puzzle, _solution| {
+        try_royalty_transfer_layer(allocator, puzzle)
+    }),
+    ("augmented_condition_layer", try_augmented_condition_layer),
+    ("bulletin_layer", try_bulletin_layer),
+    ("option_contract_layer", try_option_contract_layer),
+    ("revocation_layer", try_revocation_layer),
+    ("p2_singleton_layer", try_p2_singleton_layer),
+    ("p2_curried_layer", try_p2_curried_layer),
+    ("p2_one_of_many_layer", try_p2_one_of_many_layer),
+    ("p2_delegated_conditions_layer", try_p2_delegated_conditions_layer),
+    ("settlement_layer", try_settlement_layer),
+    ("stream_layer", try_stream_layer),
+    ("standard_layer", try_standard_layer),
+    ("p2_delegated_puzzle_layer", try_p2_delegated_puzzle_layer),
+];
+
+/// Layers whose inner puzzle is revealed by the solution (a merkle leaf, the
+/// hidden or inner path, or a curried puzzle hash), so two spends of the same
+/// puzzle hash can continue differently below them.
+pub(crate) const SOLUTION_DEPENDENT_LAYERS: &[&str] =
+    &["revocation_layer", "p2_curried_layer", "p2_one_of_many_layer"];
+
+/// Runs every detector against `puzzle`, or only the one named `only` when
+/// the layer at this depth is already known.
 fn collect_matches(
     allocator: &Allocator,
     puzzle: DriverPuzzle,
     solution: Option<NodePtr>,
+    only: Option<&str>,
 ) -> Vec<LayerMatch> {
-    let mut matches = Vec::new();
-
-    if let Some(matched) = try_cat_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_singleton_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_did_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_nft_state_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_nft_ownership_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_royalty_transfer_layer(allocator, puzzle) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_augmented_condition_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_bulletin_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_option_contract_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_revocation_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_p2_singleton_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_p2_curried_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_p2_one_of_many_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_p2_delegated_conditions_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_settlement_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_stream_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-    if let Some(matched) = try_standard_layer(allocator, puzzle, solution) {
-        matches.push(matched);
-    }
-
-    matches
+    DETECTORS
+        .iter()
+        .filter(|(name, _)| only.is_none_or(|only| only == *name))
+        .filter_map(|(_, detect)| detect(allocator, puzzle, solution))
+        .collect()
 }
 
 fn try_cat_layer(
@@ -216,16 +391,34 @@ fn try_cat_layer(
 ) -> Option<LayerMatch> {
     let layer = CatLayer::<DriverPuzzle>::parse_puzzle(allocator, puzzle).ok()??;
     let mut parse_error = None;
+    let mut params = json!({
+        "asset_id": encode_hex_prefixed(layer.asset_id.as_ref()),
+    });
     let (next_solution, solution_json) = match solution {
         Some(ptr) => match CatLayer::<DriverPuzzle>::parse_solution(allocator, ptr) {
-            Ok(parsed) => (
-                Some(parsed.inner_puzzle_solution),
-                json!({
-                    "status": "ok",
-                    "inner_solution_tree_hash": node_tree_hash_hex(allocator, parsed.inner_puzzle_solution),
-                    "parsed_debug": format!("{parsed:?}"),
-                }),
-            ),
+            Ok(parsed) => {
+                let inner_puzzle = layer.inner_puzzle.ptr();
+                if let Some(tail) =
+                    revealed_tail(allocator, inner_puzzle, parsed.inner_puzzle_solution)
+                {
+                    params["tail_kind"] = json!(tail.kind);
+                    params["tail_mod_hash"] = json!(encode_tree_hash(tail.mod_hash.as_ref()));
+                    let asset_id: &[u8] = layer.asset_id.as_ref();
+                    let tail_hash: &[u8] = tail.tree_hash.as_ref();
+                    params["tail_matches_asset_id"] = json!(tail_hash == asset_id);
+                }
+                if let Some(change) = supply_change(parsed.extra_delta) {
+                    params["supply_change"] = change;
+                }
+                (
+                    Some(parsed.inner_puzzle_solution),
+                    json!({
+                        "status": "ok",
+                        "inner_solution_tree_hash": node_tree_hash_hex(allocator, parsed.inner_puzzle_solution),
+                        "parsed_debug": format!("{parsed:?}"),
+                    }),
+                )
+            }
             Err(err) => {
                 parse_error = Some(format!("failed to parse CAT solution: {err}"));
                 (
@@ -242,10 +435,10 @@ fn try_cat_layer(
 
     Some(LayerMatch {
         name: "cat_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/cat_layer.rs",
-        params: json!({
-            "asset_id": encode_hex_prefixed(layer.asset_id.as_ref()),
-        }),
+        params,
         next_puzzle: Some(layer.inner_puzzle),
         next_solution,
         solution: solution_json,
@@ -253,6 +446,157 @@ fn try_cat_layer(
     })
 }
 
+/// Reads the CAT solution's `extra_delta` as a statement about supply: a
+/// positive delta mints that many new token mojos and a negative one melts
+/// them. Ordinary transfers carry a zero delta and get nothing.
+fn supply_change(extra_delta: i64) -> Option<Value> {
+    let amount = extra_delta.unsigned_abs();
+    let (kind, summary) = match extra_delta.signum() {
+        1 => ("mint", format!("minting {amount} new token mojos")),
+        -1 => ("melt", format!("melting {amount} token mojos")),
+        _ => return None,
+    };
+    Some(json!({
+        "kind": kind,
+        "amount_mojos": amount,
+        "summary": summary,
+    }))
+}
+
+struct RevealedTail {
+    kind: &'static str,
+    mod_hash: TreeHash,
+    tree_hash: TreeHash,
+}
+
+/// Runs a CAT's inner puzzle to find a TAIL revealed through the magic
+/// `(51 _ -113 tail_reveal tail_solution)` condition, and classifies the
+/// reveal by mod hash against [`KNOWN_TAILS`]. Kinds outside that set are
+/// reported as `unknown`.
+fn revealed_tail(
+    allocator: &Allocator,
+    inner_puzzle: NodePtr,
+    inner_solution: NodePtr,
+) -> Option<RevealedTail> {
+    let (run_allocator, conditions) = run_for_conditions(allocator, inner_puzzle, inner_solution)?;
+    conditions.iter().find_map(|items| {
+        let [opcode, _, amount, tail, ..] = items[..] else {
+            return None;
+        };
+        if !atom_is(&run_allocator, opcode, &[51]) || !atom_is(&run_allocator, amount, &[0x8f]) {
+            return None;
+        }
+        let mod_hash = DriverPuzzle::parse(&run_allocator, tail).mod_hash();
+        let kind = KNOWN_TAILS
+            .iter()
+            .find(|(_, hash)| TreeHash::new(*hash) == mod_hash)
+            .map_or("unknown", |(kind, _)| kind);
+        Some(RevealedTail {
+            kind,
+            mod_hash,
+            tree_hash: tree_hash(&run_allocator, tail),
+        })
+    })
+}
+
+/// Runs `puzzle` against `solution` and returns the scratch allocator with
+/// the leading items (up to four) of each emitted condition. The recognizer
+/// only holds a shared allocator, so the run happens in a fresh one.
+fn run_for_conditions(
+    allocator: &Allocator,
+    puzzle: NodePtr,
+    solution: NodePtr,
+) -> Option<(Allocator, Vec<Vec<NodePtr>>)> {
+    let mut run_allocator = Allocator::new();
+    let puzzle_bytes = node_to_bytes(allocator, puzzle).ok()?;
+    let solution_bytes = node_to_bytes(allocator, solution).ok()?;
+    let puzzle = node_from_bytes_backrefs(&mut run_allocator, &puzzle_bytes).ok()?;
+    let solution = node_from_bytes_backrefs(&mut run_allocator, &solution_bytes).ok()?;
+    let reduction = run_program(
+        &mut run_allocator,
+        &ChiaDialect::new(0),
+        puzzle,
+        solution,
+        DEFAULT_MAX_COST,
+    )
+    .ok()?;
+
+    let mut conditions = Vec::new();
+    let mut rest = reduction.1;
+    while let Some((condition, next_condition)) = run_allocator.next(rest) {
+        rest = next_condition;
+        let mut items = Vec::with_capacity(4);
+        let mut args = condition;
+        while let Some((item, next)) = run_allocator.next(args) {
+            items.push(item);
+            if items.len() == 4 {
+                break;
+            }
+            args = next;
+        }
+        conditions.push(items);
+    }
+    Some((run_allocator, conditions))
+}
+
+/// Compares the DID's odd (recreating) CREATE_COIN with the inner puzzle
+/// hash the DID would have if only its p2 puzzle moved: the current one or
+/// the one hinted in the first memo. A mismatch means the curried metadata
+/// changed. The new metadata is only revealed when the child is spent, so
+/// its hash is reported only when unchanged.
+fn did_metadata_update(
+    allocator: &Allocator,
+    layer: &DidLayer<NodePtr, DriverPuzzle>,
+    inner_solution: NodePtr,
+) -> Option<Value> {
+    let (run_allocator, conditions) =
+        run_for_conditions(allocator, layer.inner_puzzle.ptr(), inner_solution)?;
+    let (puzzle_hash, memos) = conditions.iter().find_map(|items| {
+        let [opcode, puzzle_hash, amount, ..] = items[..] else {
+            return None;
+        };
+        let amount = atom_bytes(&run_allocator, amount)?;
+        let odd = amount.first().is_some_and(|byte| byte & 0x80 == 0)
+            && amount.last().is_some_and(|byte| byte & 1 == 1);
+        if !atom_is(&run_allocator, opcode, &[51]) || !odd {
+            return None;
+        }
+        Some((atom_bytes(&run_allocator, puzzle_hash)?, items.get(3).copied()))
+    })?;
+    let hinted_p2_hash = memos
+        .and_then(|memos| run_allocator.next(memos))
+        .and_then(|(memo, _)| atom_bytes(&run_allocator, memo))
+        .and_then(|memo| <[u8; 32]>::try_from(memo.as_slice()).ok())
+        .map(TreeHash::new);
+
+    let metadata_hash = tree_hash(allocator, layer.metadata);
+    let unchanged = [Some(layer.inner_puzzle.curried_puzzle_hash()), hinted_p2_hash]
+        .into_iter()
+        .flatten()
+        .any(|p2_hash| {
+            let expected = DidArgs::curry_tree_hash(
+                p2_hash,
+                layer.recovery_list_hash,
+                layer.num_verifications_required,
+                SingletonStruct::new(layer.launcher_id),
+                metadata_hash,
+            );
+            expected.as_ref() == puzzle_hash.as_slice()
+        });
+    Some(json!({
+        "metadata_updated": !unchanged,
+        "new_metadata_tree_hash": unchanged.then(|| encode_tree_hash(metadata_hash.as_ref())),
+    }))
+}
+
+fn atom_bytes(allocator: &Allocator, ptr: NodePtr) -> Option<Vec<u8>> {
+    matches!(allocator.sexp(ptr), SExp::Atom).then(|| allocator.atom(ptr).as_ref().to_vec())
+}
+
+fn atom_is(allocator: &Allocator, ptr: NodePtr, bytes: &[u8]) -> bool {
+    matches!(allocator.sexp(ptr), SExp::Atom) && allocator.atom(ptr).as_ref() == bytes
+}
+
 fn try_singleton_layer(
     allocator: &Allocator,
     puzzle: DriverPuzzle,
@@ -288,6 +632,8 @@ fn try_singleton_layer(
 
     Some(LayerMatch {
         name: "singleton_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/singleton_layer.rs",
         params: json!({
             "launcher_id": encode_hex_prefixed(layer.launcher_id.as_ref()),
@@ -308,16 +654,20 @@ fn try_did_layer(
         .ok()
         .flatten()?;
     let mut parse_error = None;
+    let mut metadata_update = None;
     let (next_solution, solution_json) = match solution {
         Some(ptr) => match DidLayer::<NodePtr, DriverPuzzle>::parse_solution(allocator, ptr) {
-            Ok(DidSolution::Spend(inner_solution)) => (
-                Some(inner_solution),
-                json!({
-                    "status": "ok",
-                    "kind": "spend",
-                    "inner_solution_tree_hash": node_tree_hash_hex(allocator, inner_solution),
-                }),
-            ),
+            Ok(DidSolution::Spend(inner_solution)) => {
+                metadata_update = did_metadata_update(allocator, &layer, inner_solution);
+                (
+                    Some(inner_solution),
+                    json!({
+                        "status": "ok",
+                        "kind": "spend",
+                        "inner_solution_tree_hash": node_tree_hash_hex(allocator, inner_solution),
+                    }),
+                )
+            }
             Ok(DidSolution::Recover(recovery)) => (
                 None,
                 json!({
@@ -340,15 +690,22 @@ fn try_did_layer(
         None => (None, json!({ "status": "missing_solution" })),
     };
 
+    let mut params = json!({
+        "launcher_id": encode_hex_prefixed(layer.launcher_id.as_ref()),
+        "recovery_list_hash": layer.recovery_list_hash.map(|h| encode_hex_prefixed(h.as_ref())),
+        "num_verifications_required": layer.num_verifications_required,
+        "metadata_tree_hash": node_tree_hash_hex(allocator, layer.metadata),
+    });
+    if let (Some(Value::Object(update)), Value::Object(params)) = (metadata_update, &mut params) {
+        params.extend(update);
+    }
+
     Some(LayerMatch {
         name: "did_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/did_layer.rs",
-        params: json!({
-            "launcher_id": encode_hex_prefixed(layer.launcher_id.as_ref()),
-            "recovery_list_hash": layer.recovery_list_hash.map(|h| encode_hex_prefixed(h.as_ref())),
-            "num_verifications_required": layer.num_verifications_required,
-            "metadata_tree_hash": node_tree_hash_hex(allocator, layer.metadata),
-        }),
+        params,
         next_puzzle: Some(layer.inner_puzzle),
         next_solution,
         solution: solution_json,
@@ -391,6 +748,8 @@ fn try_nft_state_layer(
 
     Some(LayerMatch {
         name: "nft_state_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/nft_state_layer.rs",
         params: json!({
             "metadata_updater_puzzle_hash": encode_hex_prefixed(layer.metadata_updater_puzzle_hash.as_ref()),
@@ -440,6 +799,8 @@ fn try_nft_ownership_layer(
 
     Some(LayerMatch {
         name: "nft_ownership_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/nft_ownership_layer.rs",
         params: json!({
             "current_owner": layer.current_owner.map(|owner| encode_hex_prefixed(owner.as_ref())),
@@ -458,6 +819,8 @@ fn try_royalty_transfer_layer(allocator: &Allocator, puzzle: DriverPuzzle) -> Op
         .flatten()?;
     Some(LayerMatch {
         name: "royalty_transfer_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/royalty_transfer_layer.rs",
         params: json!({
             "launcher_id": encode_hex_prefixed(layer.launcher_id.as_ref()),
@@ -511,9 +874,13 @@ fn try_augmented_condition_layer(
 
     Some(LayerMatch {
         name: "augmented_condition_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/augmented_condition_layer.rs",
         params: json!({
-            "condition_debug": format!("{:?}", layer.condition),
+            "condition": augmented_condition(allocator, puzzle),
+            "forced": true,
+            "note": "prepended to the inner puzzle's conditions regardless of the inner solution",
         }),
         next_puzzle: Some(layer.inner_puzzle),
         next_solution,
@@ -522,6 +889,19 @@ fn try_augmented_condition_layer(
     })
 }
 
+/// The layer's typed condition can't be re-encoded without a mutable
+/// allocator, so the curried condition node is read back from the puzzle.
+fn augmented_condition(allocator: &Allocator, puzzle: DriverPuzzle) -> Value {
+    puzzle
+        .as_curried()
+        .and_then(|curried| AugmentedConditionArgs::from_clvm(allocator, curried.args).ok())
+        .and_then(|args| condition_info_from_node(allocator, args.condition))
+        .map_or(Value::Null, |info| json!(info))
+}
+
+/// A bulletin's params are the values it commits to besides its inner
+/// puzzle (`nonce`) and, given a solution, the messages it posts: the args
+/// of each REMARK the spend emits, as `{hex}` plus `text` when printable.
 fn try_bulletin_layer(
     allocator: &Allocator,
     puzzle: DriverPuzzle,
@@ -530,16 +910,25 @@ fn try_bulletin_layer(
     let layer = BulletinLayer::<DriverPuzzle>::parse_puzzle(allocator, puzzle)
         .ok()
         .flatten()?;
+    let mut params = json!({});
+    if let Some(nonce) = bulletin_nonce(allocator, puzzle, layer.inner_puzzle.ptr()) {
+        params["nonce"] = nonce;
+    }
     let mut parse_error = None;
     let (next_solution, solution_json) = match solution {
         Some(ptr) => match BulletinLayer::<DriverPuzzle>::parse_solution(allocator, ptr) {
-            Ok(inner_solution) => (
-                Some(inner_solution),
-                json!({
-                    "status": "ok",
-                    "inner_solution_tree_hash": node_tree_hash_hex(allocator, inner_solution),
-                }),
-            ),
+            Ok(inner_solution) => {
+                if let Some(messages) = bulletin_messages(allocator, puzzle.ptr(), ptr) {
+                    params["messages"] = messages;
+                }
+                (
+                    Some(inner_solution),
+                    json!({
+                        "status": "ok",
+                        "inner_solution_tree_hash": node_tree_hash_hex(allocator, inner_solution),
+                    }),
+                )
+            }
             Err(err) => {
                 parse_error = Some(format!("failed to parse bulletin solution: {err}"));
                 (
@@ -556,10 +945,10 @@ fn try_bulletin_layer(
 
     Some(LayerMatch {
         name: "bulletin_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/bulletin_layer.rs",
-        params: json!({
-            "nonce": "bulletin",
-        }),
+        params,
         next_puzzle: Some(layer.inner_puzzle),
         next_solution,
         solution: solution_json,
@@ -567,6 +956,51 @@ fn try_bulletin_layer(
     })
 }
 
+/// The curried values other than the inner puzzle (or its hash): an atom
+/// is shown as hex, anything else by its tree hash.
+fn bulletin_nonce(allocator: &Allocator, puzzle: DriverPuzzle, inner_puzzle: NodePtr) -> Option<Value> {
+    let args = curried_args(allocator, puzzle.as_curried()?.args)?;
+    let inner_hash = tree_hash(allocator, inner_puzzle);
+    let committed = args
+        .into_iter()
+        .filter(|&arg| {
+            tree_hash(allocator, arg) != inner_hash
+                && !atom_is(allocator, arg, inner_hash.as_ref())
+        })
+        .map(|arg| match atom_bytes(allocator, arg) {
+            Some(bytes) => json!(encode_hex_prefixed(&bytes)),
+            None => json!(node_tree_hash_hex(allocator, arg)),
+        })
+        .collect::<Vec<_>>();
+    match committed.len() {
+        0 => None,
+        1 => committed.into_iter().next(),
+        _ => Some(Value::Array(committed)),
+    }
+}
+
+fn bulletin_messages(allocator: &Allocator, puzzle: NodePtr, solution: NodePtr) -> Option<Value> {
+    let (run_allocator, conditions) = run_for_conditions(allocator, puzzle, solution)?;
+    let messages = conditions
+        .iter()
+        .filter(|items| items.first().is_some_and(|&opcode| atom_is(&run_allocator, opcode, &[1])))
+        .map(|items| {
+            items[1..]
+                .iter()
+                .filter_map(|&arg| atom_bytes(&run_allocator, arg))
+                .map(|bytes| {
+                    let mut part = json!({ "hex": encode_hex_prefixed(&bytes) });
+                    if let Some(text) = printable_memo_text(&bytes) {
+                        part["text"] = json!(text);
+                    }
+                    part
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    Some(json!(messages))
+}
+
 fn try_option_contract_layer(
     allocator: &Allocator,
     puzzle: DriverPuzzle,
@@ -602,6 +1036,8 @@ fn try_option_contract_layer(
 
     Some(LayerMatch {
         name: "option_contract_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/option_contract_layer.rs",
         params: json!({
             "underlying_coin_id": encode_hex_prefixed(layer.underlying_coin_id.as_ref()),
@@ -623,18 +1059,41 @@ fn try_revocation_layer(
         .ok()
         .flatten()?;
     let mut parse_error = None;
+    let mut next_puzzle = None;
     let (next_solution, solution_json) = match solution {
         Some(ptr) => match RevocationLayer::parse_solution(allocator, ptr) {
-            Ok(parsed) => (
-                None,
-                json!({
+            Ok(parsed) => {
+                let revealed_hash = tree_hash(allocator, parsed.puzzle);
+                let committed_hash = if parsed.hidden {
+                    layer.hidden_puzzle_hash
+                } else {
+                    layer.inner_puzzle_hash
+                };
+                let matches_commitment = revealed_hash.as_ref() == committed_hash.as_ref();
+                let mut solution_json = json!({
                     "status": "ok",
                     "hidden": parsed.hidden,
-                    "puzzle_tree_hash": node_tree_hash_hex(allocator, parsed.puzzle),
+                    "path": if parsed.hidden { "hidden" } else { "inner" },
+                    "puzzle_tree_hash": encode_tree_hash(revealed_hash.as_ref()),
                     "solution_tree_hash": node_tree_hash_hex(allocator, parsed.solution),
+                    "matches_committed_hash": matches_commitment,
                     "parsed_debug": format!("{parsed:?}"),
-                }),
-            ),
+                });
+                if parsed.hidden {
+                    // The revocation path hands control to the hidden puzzle,
+                    // so show it in full rather than recursing as if it were
+                    // the owner's inner puzzle.
+                    solution_json["hidden_puzzle_disasm"] = json!(disassemble(
+                        allocator,
+                        parsed.puzzle,
+                        Some(OPERATORS_LATEST_VERSION)
+                    ));
+                    (None, solution_json)
+                } else {
+                    next_puzzle = Some(DriverPuzzle::parse(allocator, parsed.puzzle));
+                    (Some(parsed.solution), solution_json)
+                }
+            }
             Err(err) => {
                 parse_error = Some(format!("failed to parse revocation solution: {err}"));
                 (
@@ -651,12 +1110,14 @@ fn try_revocation_layer(
 
     Some(LayerMatch {
         name: "revocation_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/revocation_layer.rs",
         params: json!({
             "hidden_puzzle_hash": encode_hex_prefixed(layer.hidden_puzzle_hash.as_ref()),
             "inner_puzzle_hash": encode_hex_prefixed(layer.inner_puzzle_hash.as_ref()),
         }),
-        next_puzzle: None,
+        next_puzzle,
         next_solution,
         solution: solution_json,
         parse_error,
@@ -698,6 +1159,8 @@ fn try_p2_singleton_layer(
 
     Some(LayerMatch {
         name: "p2_singleton_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/p2_singleton_layer.rs",
         params: json!({
             "launcher_id": encode_hex_prefixed(layer.launcher_id.as_ref()),
@@ -716,17 +1179,21 @@ fn try_p2_curried_layer(
 ) -> Option<LayerMatch> {
     let layer = P2CurriedLayer::parse_puzzle(allocator, puzzle).ok().flatten()?;
     let mut parse_error = None;
+    let mut next_puzzle = None;
     let (next_solution, solution_json) = match solution {
         Some(ptr) => match P2CurriedLayer::parse_solution(allocator, ptr) {
-            Ok(parsed) => (
-                None,
-                json!({
-                    "status": "ok",
-                    "puzzle_tree_hash": node_tree_hash_hex(allocator, parsed.puzzle),
-                    "solution_tree_hash": node_tree_hash_hex(allocator, parsed.solution),
-                    "parsed_debug": format!("{parsed:?}"),
-                }),
-            ),
+            Ok(parsed) => {
+                next_puzzle = Some(DriverPuzzle::parse(allocator, parsed.puzzle));
+                (
+                    Some(parsed.solution),
+                    json!({
+                        "status": "ok",
+                        "puzzle_tree_hash": node_tree_hash_hex(allocator, parsed.puzzle),
+                        "solution_tree_hash": node_tree_hash_hex(allocator, parsed.solution),
+                        "parsed_debug": format!("{parsed:?}"),
+                    }),
+                )
+            }
             Err(err) => {
                 parse_error = Some(format!("failed to parse p2_curried solution: {err}"));
                 (
@@ -743,11 +1210,13 @@ fn try_p2_curried_layer(
 
     Some(LayerMatch {
         name: "p2_curried_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/p2_curried_layer.rs",
         params: json!({
             "puzzle_hash": encode_hex_prefixed(layer.puzzle_hash.as_ref()),
         }),
-        next_puzzle: None,
+        next_puzzle,
         next_solution,
         solution: solution_json,
         parse_error,
@@ -763,17 +1232,21 @@ fn try_p2_one_of_many_layer(
         .ok()
         .flatten()?;
     let mut parse_error = None;
+    let mut next_puzzle = None;
     let (next_solution, solution_json) = match solution {
         Some(ptr) => match P2OneOfManyLayer::parse_solution(allocator, ptr) {
-            Ok(parsed) => (
-                None,
-                json!({
-                    "status": "ok",
-                    "puzzle_tree_hash": node_tree_hash_hex(allocator, parsed.puzzle),
-                    "solution_tree_hash": node_tree_hash_hex(allocator, parsed.solution),
-                    "parsed_debug": format!("{parsed:?}"),
-                }),
-            ),
+            Ok(parsed) => {
+                next_puzzle = Some(DriverPuzzle::parse(allocator, parsed.puzzle));
+                (
+                    Some(parsed.solution),
+                    json!({
+                        "status": "ok",
+                        "puzzle_tree_hash": node_tree_hash_hex(allocator, parsed.puzzle),
+                        "solution_tree_hash": node_tree_hash_hex(allocator, parsed.solution),
+                        "parsed_debug": format!("{parsed:?}"),
+                    }),
+                )
+            }
             Err(err) => {
                 parse_error = Some(format!("failed to parse p2_one_of_many solution: {err}"));
                 (
@@ -790,11 +1263,13 @@ fn try_p2_one_of_many_layer(
 
     Some(LayerMatch {
         name: "p2_one_of_many_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/p2_one_of_many_layer.rs",
         params: json!({
             "merkle_root": encode_hex_prefixed(layer.merkle_root.as_ref()),
         }),
-        next_puzzle: None,
+        next_puzzle,
         next_solution,
         solution: solution_json,
         parse_error,
@@ -836,6 +1311,8 @@ fn try_p2_delegated_conditions_layer(
 
     Some(LayerMatch {
         name: "p2_delegated_conditions_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/p2_delegated_conditions_layer.rs",
         params: json!({
             "public_key": encode_hex_prefixed(&layer.public_key.to_bytes()),
@@ -858,14 +1335,41 @@ fn try_settlement_layer(
     let mut parse_error = None;
     let (next_solution, solution_json) = match solution {
         Some(ptr) => match SettlementLayer::parse_solution(allocator, ptr) {
-            Ok(parsed) => (
-                None,
-                json!({
-                    "status": "ok",
-                    "payments_len": parsed.notarized_payments.len(),
-                    "parsed_debug": format!("{parsed:?}"),
-                }),
-            ),
+            Ok(parsed) => {
+                let memos = settlement_payment_memos(allocator, ptr);
+                let memos_of = |notarized_idx: usize, payment_idx: usize| {
+                    memos
+                        .get(notarized_idx)
+                        .and_then(|payments| payments.get(payment_idx))
+                        .cloned()
+                        .unwrap_or_default()
+                };
+                (
+                    None,
+                    json!({
+                        "status": "ok",
+                        "payments_len": parsed.notarized_payments.len(),
+                        "notarized_payments": parsed
+                            .notarized_payments
+                            .iter()
+                            .enumerate()
+                            .map(|(notarized_idx, notarized)| json!({
+                                "nonce": encode_hex_prefixed(notarized.nonce.as_ref()),
+                                "payments": notarized
+                                    .payments
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(payment_idx, payment)| json!({
+                                        "puzzle_hash": encode_hex_prefixed(payment.puzzle_hash.as_ref()),
+                                        "amount": payment.amount,
+                                        "memos": memos_of(notarized_idx, payment_idx),
+                                    }))
+                                    .collect::<Vec<_>>(),
+                            }))
+                            .collect::<Vec<_>>(),
+                    }),
+                )
+            }
             Err(err) => {
                 parse_error = Some(format!("failed to parse settlement solution: {err}"));
                 (
@@ -882,6 +1386,8 @@ fn try_settlement_layer(
 
     Some(LayerMatch {
         name: "settlement_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/settlement_layer.rs",
         params: json!({}),
         next_puzzle: None,
@@ -891,6 +1397,36 @@ fn try_settlement_layer(
     })
 }
 
+/// Reads each notarized payment's memo list straight from the settlement
+/// solution `((nonce (puzzle_hash amount (memo ...)) ...) ...)`, indexed the
+/// same way as the parsed payments. Non-atom memos are skipped.
+fn settlement_payment_memos(allocator: &Allocator, solution: NodePtr) -> Vec<Vec<Vec<String>>> {
+    let list = |mut ptr: NodePtr| {
+        std::iter::from_fn(move || {
+            let (first, rest) = allocator.next(ptr)?;
+            ptr = rest;
+            Some(first)
+        })
+    };
+    let Some((notarized_payments, _)) = allocator.next(solution) else {
+        return Vec::new();
+    };
+    list(notarized_payments)
+        .map(|notarized| {
+            let payments = allocator.next(notarized).map_or(NodePtr::NIL, |(_, rest)| rest);
+            list(payments)
+                .map(|payment| {
+                    let memo_list = list(payment).nth(2).unwrap_or(NodePtr::NIL);
+                    list(memo_list)
+                        .filter_map(|memo| atom_bytes(allocator, memo))
+                        .map(|memo| encode_hex_prefixed(&memo))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
 fn try_stream_layer(
     allocator: &Allocator,
     puzzle: DriverPuzzle,
@@ -926,6 +1462,8 @@ fn try_stream_layer(
 
     Some(LayerMatch {
         name: "stream_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/streaming_layer.rs",
         params: json!({
             "recipient": encode_hex_prefixed(layer.recipient.as_ref()),
@@ -975,6 +1513,8 @@ fn try_standard_layer(
 
     Some(LayerMatch {
         name: "standard_layer",
+        source_repo: SOURCE_REPO,
+        source_ref: SOURCE_REF,
         source_path: "crates/chia-sdk-driver/src/layers/standard_layer.rs",
         params: json!({
             "synthetic_key": encode_hex_prefixed(&layer.synthetic_key.to_bytes()),
@@ -986,13 +1526,155 @@ fn try_standard_layer(
     })
 }
 
+/// The bare p2_delegated_puzzle has no hidden-puzzle path, so it is keyed on
+/// its exact mod hash to stay distinct from the standard layer.
+fn try_p2_delegated_puzzle_layer(
+    allocator: &Allocator,
+    puzzle: DriverPuzzle,
+    solution: Option<NodePtr>,
+) -> Option<LayerMatch> {
+    let curried = puzzle.as_curried()?;
+    if curried.mod_hash != TreeHash::new(P2_DELEGATED_PUZZLE_HASH) {
+        return None;
+    }
+    let args = P2DelegatedPuzzleArgs::from_clvm(allocator, curried.args).ok()?;
+    let mut parse_error = None;
+    let (next_solution, solution_json) = match solution {
+        Some(ptr) => match P2DelegatedPuzzleSolution::from_clvm(allocator, ptr) {
+            Ok(parsed) => (
+                None,
+                json!({
+                    "status": "ok",
+                    "delegated_puzzle_tree_hash": node_tree_hash_hex(allocator, parsed.delegated_puzzle),
+                    "delegated_solution_tree_hash": node_tree_hash_hex(allocator, parsed.delegated_solution),
+                }),
+            ),
+            Err(err) => {
+                parse_error = Some(format!("failed to parse p2_delegated_puzzle solution: {err}"));
+                (
+                    None,
+                    json!({
+                        "status": "error",
+                        "message": err.to_string(),
+                    }),
+                )
+            }
+        },
+        None => (None, json!({ "status": "missing_solution" })),
+    };
+
+    Some(LayerMatch {
+        name: "p2_delegated_puzzle_layer",
+        source_repo: CHIA_PUZZLES_REPO,
+        source_ref: CHIA_PUZZLES_REF,
+        source_path: "puzzles/p2_delegated_puzzle.clsp",
+        params: json!({
+            "public_key": encode_hex_prefixed(&args.public_key.to_bytes()),
+        }),
+        next_puzzle: None,
+        next_solution,
+        solution: solution_json,
+        parse_error,
+    })
+}
+
+/// The launcher is an uncurried puzzle, so it is matched by hash. Its
+/// solution names the singleton it creates; `inspect` ties that to the eve
+/// spend when the bundle carries one.
+fn try_singleton_launcher(
+    allocator: &Allocator,
+    puzzle: DriverPuzzle,
+    solution: Option<NodePtr>,
+) -> Option<LayerMatch> {
+    if puzzle.curried_puzzle_hash() != TreeHash::new(SINGLETON_LAUNCHER_HASH) {
+        return None;
+    }
+    let mut parse_error = None;
+    let solution_json = match solution {
+        Some(ptr) => match LauncherSolution::<NodePtr>::from_clvm(allocator, ptr) {
+            Ok(parsed) => json!({
+                "status": "ok",
+                "singleton_puzzle_hash": encode_hex_prefixed(parsed.singleton_puzzle_hash.as_ref()),
+                "amount": parsed.amount,
+                "key_value_list": node_summary(allocator, parsed.key_value_list),
+            }),
+            Err(err) => {
+                parse_error = Some(format!("failed to parse singleton launcher solution: {err}"));
+                json!({
+                    "status": "error",
+                    "message": err.to_string(),
+                })
+            }
+        },
+        None => json!({ "status": "missing_solution" }),
+    };
+
+    Some(LayerMatch {
+        name: "singleton_launcher",
+        source_repo: CHIA_PUZZLES_REPO,
+        source_ref: CHIA_PUZZLES_REF,
+        source_path: "puzzles/singleton_launcher.clsp",
+        params: json!({}),
+        next_puzzle: None,
+        next_solution: None,
+        solution: solution_json,
+        parse_error,
+    })
+}
+
+fn wrapper_evidence(
+    allocator: &Allocator,
+    puzzle: DriverPuzzle,
+    matched: &LayerMatch,
+) -> WrapperEvidence {
+    let curried = puzzle.as_curried();
+    WrapperEvidence {
+        matched_by: if curried.is_some() { "curried_mod_hash" } else { "puzzle_hash" }.to_string(),
+        matched_hash: encode_tree_hash(puzzle.mod_hash().as_ref()),
+        curried_arg_count: curried.and_then(|curried| curried_arg_count(allocator, curried.args)),
+        inner_puzzle_found: matched.next_puzzle.is_some(),
+        solution_status: matched
+            .solution
+            .get("status")
+            .and_then(Value::as_str)
+            .unwrap_or("unsupported")
+            .to_string(),
+    }
+}
+
+fn curried_arg_count(allocator: &Allocator, args: NodePtr) -> Option<usize> {
+    curried_args(allocator, args).map(|args| args.len())
+}
+
+/// Unpacks the `(c (q . arg) rest)` cells of a curried argument list, which
+/// ends in the environment atom `1`.
+fn curried_args(allocator: &Allocator, mut args: NodePtr) -> Option<Vec<NodePtr>> {
+    let mut out = Vec::new();
+    loop {
+        let SExp::Pair(_, rest) = allocator.sexp(args) else {
+            return (allocator.atom(args).as_ref() == [1]).then_some(out);
+        };
+        let SExp::Pair(quoted, rest) = allocator.sexp(rest) else {
+            return None;
+        };
+        let SExp::Pair(_, arg) = allocator.sexp(quoted) else {
+            return None;
+        };
+        let SExp::Pair(next, _) = allocator.sexp(rest) else {
+            return None;
+        };
+        out.push(arg);
+        args = next;
+    }
+}
+
 fn candidate_from_match(matched: &LayerMatch, confidence: f64) -> PuzzleCandidate {
     PuzzleCandidate {
         name: matched.name.to_string(),
         confidence,
-        source_repo: Some(SOURCE_REPO.to_string()),
+        source_repo: Some(matched.source_repo.to_string()),
         source_path: Some(matched.source_path.to_string()),
-        source_hash: Some(SOURCE_REF.to_string()),
+        source_hash: Some(matched.source_ref.to_string()),
     }
 }
 
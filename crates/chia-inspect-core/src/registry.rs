@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use chia_protocol::Bytes32;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::schema::PuzzleRecognition;
+use crate::util::decode_hex;
+
+/// What a local registry knows about one CAT.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatRegistryEntry {
+    pub name: String,
+    /// Ticker-style short code, e.g. `USDS`.
+    #[serde(default)]
+    pub code: Option<String>,
+    /// TAIL kind, e.g. `genesis_by_coin_id` or `everything_with_signature`.
+    #[serde(default)]
+    pub tail_type: Option<String>,
+}
+
+/// Local asset registry mapping CAT asset ids to names, in the
+/// `{"<asset_id>": {name, code, tail_type}}` shape of `cats_by_asset_id.json`.
+/// Asset ids may carry a `0x` prefix.
+#[derive(Debug, Clone, Default)]
+pub struct CatRegistry {
+    entries: BTreeMap<Bytes32, CatRegistryEntry>,
+}
+
+impl CatRegistry {
+    pub fn from_json(registry_json: &str) -> Result<Self> {
+        let raw: BTreeMap<String, CatRegistryEntry> = serde_json::from_str(registry_json)
+            .context("CAT registry must map asset ids to {name, code, tail_type}")?;
+        let mut entries = BTreeMap::new();
+        for (asset_id, entry) in raw {
+            let bytes = decode_hex(&asset_id)?;
+            let asset_id = Bytes32::try_from(bytes.as_slice())
+                .with_context(|| format!("CAT registry key is not a 32-byte asset id: {asset_id}"))?;
+            entries.insert(asset_id, entry);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, asset_id: &str) -> Option<&CatRegistryEntry> {
+        let bytes = decode_hex(asset_id).ok()?;
+        self.entries.get(&Bytes32::try_from(bytes.as_slice()).ok()?)
+    }
+
+    /// Sets `params.registry` on every `cat_layer` wrapper to the registry
+    /// entry for its asset id, or `null` when the asset is not listed.
+    pub fn annotate(&self, recognition: &mut PuzzleRecognition) {
+        for wrapper in &mut recognition.wrappers {
+            if wrapper.name != "cat_layer" {
+                continue;
+            }
+            let entry = wrapper
+                .params
+                .get("asset_id")
+                .and_then(Value::as_str)
+                .and_then(|asset_id| self.get(asset_id));
+            if let Value::Object(params) = &mut wrapper.params {
+                params.insert("registry".to_string(), json!(entry));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_with_or_without_prefix() {
+        let asset_id = "ab".repeat(32);
+        let json = format!(r#"{{"{asset_id}": {{"code": "USDS", "name": "Stably USD"}}}}"#);
+        let registry = CatRegistry::from_json(&json).expect("registry");
+        let entry = registry.get(&format!("0x{asset_id}")).expect("entry");
+        assert_eq!(entry.code.as_deref(), Some("USDS"));
+        assert!(entry.tail_type.is_none());
+        assert!(registry.get(&"cd".repeat(32)).is_none());
+    }
+}
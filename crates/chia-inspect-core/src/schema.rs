@@ -1,7 +1,16 @@
-use serde::Serialize;
-use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
 
-#[derive(Debug, Clone, Serialize)]
+use chia_sha2::Sha256;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::error::InspectError;
+use crate::util::encode_hex_prefixed;
+
+pub const SCHEMA_VERSION: &str = "chia.inspect.spendbundle.v2";
+pub const SUPPORTED_SCHEMA_VERSIONS: &[&str] = &[SCHEMA_VERSION];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InspectionOutput {
     pub schema_version: String,
     pub tool: ToolInfo,
@@ -10,78 +19,286 @@ pub struct InspectionOutput {
     pub result: ResultInfo,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl InspectionOutput {
+    /// Sha256 over the semantically meaningful parts of the result: bundle
+    /// status, summary, and each spend's status, layer stack (names, mod
+    /// hashes and params) and conditions. Tool version, source refs, display
+    /// fields and the digest itself are left out, so re-inspecting a bundle
+    /// with a newer tool keeps the digest unless the analysis changed.
+    pub fn semantic_digest(&self) -> String {
+        let spends = self
+            .result
+            .spends
+            .iter()
+            .map(|spend| {
+                let wrappers = spend
+                    .puzzle
+                    .recognition
+                    .wrappers
+                    .iter()
+                    .map(|wrapper| {
+                        json!({
+                            "name": wrapper.name,
+                            "mod_hash": wrapper.mod_hash,
+                            "params": wrapper.params,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let conditions = spend
+                    .evaluation
+                    .conditions
+                    .iter()
+                    .map(|condition| json!({ "opcode": condition.opcode, "args": condition.args }))
+                    .collect::<Vec<_>>();
+                json!({
+                    "coin_id": spend.coin_spend.coin.coin_id,
+                    "status": spend.evaluation.status,
+                    "wrappers": wrappers,
+                    "conditions": conditions,
+                })
+            })
+            .collect::<Vec<_>>();
+        // Object keys serialize sorted, so this string is canonical.
+        let canonical = json!({
+            "status": self.result.status,
+            "summary": self.result.summary,
+            "spends": spends,
+        });
+        let mut hasher = Sha256::new();
+        hasher.update(canonical.to_string().as_bytes());
+        encode_hex_prefixed(&hasher.finalize())
+    }
+
+    /// Every condition named `opcode` (e.g. `CREATE_COIN`) across all
+    /// spends, paired with the index of the spend that emitted it.
+    pub fn conditions_of<'a>(
+        &'a self,
+        opcode: &'a str,
+    ) -> impl Iterator<Item = (usize, &'a ConditionInfo)> + 'a {
+        self.result
+            .spends
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, spend)| spend.evaluation.conditions.iter().map(move |c| (idx, c)))
+            .filter(move |(_, condition)| condition.opcode == opcode)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolInfo {
     pub name: String,
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkInfo {
     pub name: String,
     pub genesis_challenge: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputInfo {
     pub source: SourceInfo,
     pub notes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceInfo {
     pub kind: String,
     pub value: Option<String>,
     pub rpc: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResultInfo {
     pub status: String,
+    /// Spend bundle name as used by the mempool, for correlating with mempool entries.
+    #[serde(default)]
+    pub bundle_id: String,
+    /// [`InspectionOutput::semantic_digest`], for detecting changed analyses.
+    #[serde(default)]
+    pub digest: String,
     pub error: Option<ErrorInfo>,
     pub summary: Summary,
     pub spends: Vec<SpendAnalysis>,
     pub signatures: SignatureSummary,
     pub offer: Option<Value>,
+    #[serde(default)]
+    pub readiness: Readiness,
+    #[serde(default)]
+    pub warnings: Vec<WarningInfo>,
+    /// Every BLS public key referenced by signatures or recognized layers.
+    #[serde(default)]
+    pub public_keys: BTreeSet<String>,
+    /// Every CAT asset id a recognized `cat_layer` carries.
+    #[serde(default)]
+    pub asset_ids: BTreeSet<String>,
+    /// How much of the bundle layer recognition understood.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<RecognitionCoverage>,
+    /// Diagnostics requested explicitly, e.g. `consensus` with the full
+    /// consensus conditions the analysis was mapped from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecognitionCoverage {
+    pub spends: usize,
+    pub fully_recognized: usize,
+    pub partially_recognized: usize,
+    pub unrecognized: usize,
+    /// Puzzle hashes of the spent coins no layer matched.
+    pub unrecognized_puzzle_hashes: BTreeSet<String>,
+    /// Inner puzzle hashes where partial recognition stopped.
+    pub unknown_inner_puzzle_hashes: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarningInfo {
+    pub code: String,
+    pub message: String,
+    pub spend_index: Option<usize>,
+    pub details: Option<Value>,
+    /// JSON pointer (RFC 6901) to the offending element of this document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+}
+
+/// Defaults to not submittable, so a document from before readiness was
+/// reported never reads as cleared for submission.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Readiness {
+    pub submittable: bool,
+    pub blockers: Vec<ReadinessBlocker>,
+    /// Fee the mempool requires at the configured fee-per-cost rate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_fee_mojos: Option<u64>,
+    /// Of the networks tried, those under which the bundle validates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub networks_accepted: Option<Vec<String>>,
+}
+
+/// The go/no-go gist of an inspection, for submission pipelines that need
+/// nothing else.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verdict {
+    pub bundle_id: String,
+    pub status: String,
+    pub submittable: bool,
+    pub fee_mojos: u64,
+    pub blockers: Vec<ReadinessBlocker>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessBlocker {
+    pub kind: String,
+    pub message: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorInfo {
     pub kind: String,
     pub message: String,
     pub details: Option<Value>,
+    /// JSON pointer (RFC 6901) to the offending element of this document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Summary {
     pub removals: Vec<CoinRef>,
     pub additions: Vec<CoinRef>,
     pub fee_mojos: u64,
     pub net_xch_delta_by_puzzle_hash: Vec<NetDelta>,
+    /// Bundle-wide totals of `spends[].evaluation.condition_counts`.
+    #[serde(default)]
+    pub condition_counts: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub cost: CostSummary,
+    /// Present when any spend emits RESERVE_FEE.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_reservation: Option<FeeReservation>,
+    /// Present when any spend emits a timelock condition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validity_window: Option<ValidityWindow>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// When the bundle can be included: every spend's timelocks intersected on
+/// the absolute timeline. `not_before_*` bounds are inclusive, `before_*`
+/// bounds exclusive, and `null` means unbounded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidityWindow {
+    pub not_before_height: Option<u64>,
+    pub not_before_seconds: Option<u64>,
+    pub before_height: Option<u64>,
+    pub before_seconds: Option<u64>,
+    /// False when no height or no timestamp satisfies every bound.
+    pub satisfiable: bool,
+    /// Spends whose relative timelocks are left out because their coin's
+    /// confirmation height or timestamp is unknown.
+    pub unresolved_relative: Vec<usize>,
+}
+
+/// The mempool's bundle-level RESERVE_FEE check: the reservations of all
+/// spends are summed and must not exceed the fee implied by the bundle's
+/// value delta.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeReservation {
+    pub reserved_total: u64,
+    pub implied_fee: u64,
+    /// Spends emitting at least one RESERVE_FEE.
+    pub spend_indices: Vec<usize>,
+}
+
+/// What the bundle costs the mempool: CLVM execution plus condition cost,
+/// and a per-byte charge on the serialized puzzle reveals and solutions.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CostSummary {
+    pub serialized_size_bytes: u64,
+    /// Sum of `spends[].evaluation.cost`.
+    pub clvm_cost: u64,
+    /// `serialized_size_bytes` times the consensus cost per byte.
+    pub byte_cost: u64,
+    pub total_cost: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetDelta {
     pub puzzle_hash: String,
     pub delta_mojos: i128,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpendAnalysis {
     pub coin_spend: CoinSpendView,
     pub puzzle: PuzzleInfo,
     pub evaluation: EvaluationInfo,
     pub puzzle_behavior: PuzzleBehavior,
+    /// Control passed to something other than the committed inner puzzle:
+    /// a revocation layer's hidden puzzle, a p2_one_of_many merkle leaf, or
+    /// a standard puzzle's hidden-puzzle path.
+    #[serde(default)]
+    pub used_escape_path: bool,
+    /// Created minus consumed amount (`evaluation.additions` minus the
+    /// spent coin), in the coin's own unit: mojos, or CAT mojos for CATs.
+    #[serde(default)]
+    pub value_balance: i128,
+    /// The spend emits at least one AGG_SIG_* condition, so it can't be
+    /// made without a signature.
+    #[serde(default)]
+    pub requires_signature: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoinSpendView {
     pub coin: CoinRef,
     pub puzzle_reveal: String,
     pub solution: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoinRef {
     pub coin_id: String,
     pub parent_coin_id: String,
@@ -89,7 +306,7 @@ pub struct CoinRef {
     pub amount: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PuzzleInfo {
     pub id: PuzzleId,
     pub recognition: PuzzleRecognition,
@@ -97,22 +314,39 @@ pub struct PuzzleInfo {
     pub solution_disasm: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PuzzleId {
     pub puzzle_hash: String,
     pub tree_hash: String,
     pub shatree: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PuzzleRecognition {
     pub recognized: bool,
     pub candidates: Vec<PuzzleCandidate>,
     pub wrappers: Vec<WrapperInfo>,
     pub parsed_solution: Option<Value>,
+    /// Name of the terminal (last) wrapper: who ultimately controls the coin.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_most_layer: Option<String>,
+    /// Set when recognition stopped at a curried puzzle no detector knows:
+    /// the outer puzzle, or the inner puzzle below the last wrapper.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unrecognized_curried: Option<CurriedPuzzleInfo>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// What uncurrying an unknown puzzle tells about it without naming it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurriedPuzzleInfo {
+    pub puzzle_hash: String,
+    pub mod_hash: String,
+    pub arg_count: usize,
+    /// Tree hash of each curried argument, in curry order.
+    pub arg_tree_hashes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WrapperInfo {
     pub name: String,
     pub source_repo: String,
@@ -123,9 +357,29 @@ pub struct WrapperInfo {
     pub inner_puzzle_tree_hash: Option<String>,
     pub params: Value,
     pub parse_error: Option<String>,
+    /// Why the recognizer accepted this layer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<WrapperEvidence>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// The checks that passed when a wrapper was recognized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapperEvidence {
+    /// `"curried_mod_hash"` when the uncurried mod hash equals the layer's
+    /// known mod hash, `"puzzle_hash"` for uncurried puzzles matched whole.
+    pub matched_by: String,
+    /// The known hash that matched.
+    pub matched_hash: String,
+    /// Number of curried arguments; `None` for uncurried puzzles.
+    pub curried_arg_count: Option<usize>,
+    /// Whether the layer exposed an inner puzzle to continue recognition with.
+    pub inner_puzzle_found: bool,
+    /// The layer's solution parse status: `ok`, `error`, `missing_solution`,
+    /// or `unsupported` for layers without a solution parser.
+    pub solution_status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PuzzleCandidate {
     pub name: String,
     pub confidence: f64,
@@ -134,7 +388,7 @@ pub struct PuzzleCandidate {
     pub source_hash: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvaluationInfo {
     pub status: String,
     pub cost: u64,
@@ -142,36 +396,44 @@ pub struct EvaluationInfo {
     pub additions: Vec<CoinRef>,
     pub announcements: Vec<Value>,
     pub assertions: Vec<Value>,
+    /// Occurrences of each condition opcode the puzzle emitted.
+    #[serde(default)]
+    pub condition_counts: BTreeMap<String, u64>,
     pub failure: Option<FailureInfo>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConditionInfo {
     pub opcode: String,
     pub args: Vec<Value>,
     pub raw: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FailureInfo {
     pub kind: String,
     pub message: String,
 }
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SignatureSummary {
     pub aggregated_signature: String,
+    /// The aggregated signature is the default (infinity) point, as in
+    /// bundles a loader built from coin spends alone. Only a bundle without
+    /// AGG_SIG conditions is validly signed this way.
+    #[serde(default)]
+    pub is_default: bool,
     pub agg_sig_me: Vec<AggSigInfo>,
     pub agg_sig_unsafe: Vec<AggSigInfo>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggSigInfo {
     pub pubkey: String,
     pub msg: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PuzzleBehavior {
     pub clvm: ClvmBehavior,
     pub static_features: StaticFeatures,
@@ -179,31 +441,56 @@ pub struct PuzzleBehavior {
     pub explanation: Explanation,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClvmBehavior {
     pub puzzle_reveal_bytes: String,
     pub solution_bytes: String,
     pub puzzle_opd: String,
     pub solution_opd: String,
     pub uses_backrefs: bool,
+    /// Length of the puzzle reveal as it arrived, in whichever encoding the
+    /// input used. Prefer `puzzle_len`/`solution_len` for fee reasoning.
+    pub serialized_len_bytes: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub puzzle_len: Option<EncodedLengths>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution_len: Option<EncodedLengths>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical: Option<CanonicalClvm>,
+}
+
+/// Serialized sizes of one CLVM tree. Fees are charged on the
+/// backref-compressed size, while cost follows the expanded tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodedLengths {
+    pub compact_len_bytes: usize,
+    pub expanded_len_bytes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanonicalClvm {
+    pub puzzle_reveal_bytes: String,
+    pub solution_bytes: String,
     pub serialized_len_bytes: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StaticFeatures {
     pub operators_used: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator_counts: Option<BTreeMap<String, u64>>,
     pub env_paths_used: Vec<u32>,
     pub constants: ConstantBuckets,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConstantBuckets {
     pub bytes32: Vec<String>,
     pub g1_pubkeys: Vec<String>,
     pub small_ints: Vec<u64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DynamicBehavior {
     pub status: String,
     pub cost: u64,
@@ -212,9 +499,26 @@ pub struct DynamicBehavior {
     pub failure: Option<FailureInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Explanation {
     pub enforced_signatures: Vec<Value>,
     pub value_flow: Vec<Value>,
+    /// Timelocks as `{kind, value, satisfied}`. `satisfied` is `null` when
+    /// unknown: no current height/timestamp was given, or a relative lock's
+    /// coin has no confirmation in the spent context.
     pub constraints: Vec<Value>,
+    /// Set for spends whose purpose is structural, e.g. `"glue"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+}
+
+/// Parses an inspection document and checks that it matches a schema version
+/// this build understands.
+pub fn validate_output_document(json: &str) -> Result<InspectionOutput, InspectError> {
+    let output: InspectionOutput =
+        serde_json::from_str(json).map_err(|err| InspectError::InvalidOutput(err.to_string()))?;
+    if !SUPPORTED_SCHEMA_VERSIONS.contains(&output.schema_version.as_str()) {
+        return Err(InspectError::UnsupportedSchemaVersion(output.schema_version));
+    }
+    Ok(output)
 }
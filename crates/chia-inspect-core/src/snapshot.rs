@@ -0,0 +1,169 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::recognize::recognize_puzzle_structure;
+use crate::schema::PuzzleRecognition;
+
+/// What recognition concludes about a puzzle from its structure alone: the
+/// layer stack with each layer's mod hash and params. Source refs are left
+/// out, so a snapshot stored before an SDK bump compares cleanly against one
+/// taken after it; only a change in what was recognized shows up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecognitionSnapshot {
+    pub layers: Vec<LayerSnapshot>,
+    /// Layers that all matched where recognition stopped as ambiguous.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ambiguous: Vec<String>,
+    /// Mod hash of the unknown curried puzzle recognition stopped at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unrecognized_mod_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerSnapshot {
+    pub name: String,
+    pub mod_hash: String,
+    pub params: Value,
+}
+
+impl From<&PuzzleRecognition> for RecognitionSnapshot {
+    fn from(recognition: &PuzzleRecognition) -> Self {
+        let recognized = recognition.wrappers.len();
+        Self {
+            layers: recognition
+                .wrappers
+                .iter()
+                .map(|wrapper| LayerSnapshot {
+                    name: wrapper.name.clone(),
+                    mod_hash: wrapper.mod_hash.clone(),
+                    params: wrapper.params.clone(),
+                })
+                .collect(),
+            ambiguous: recognition
+                .candidates
+                .iter()
+                .skip(recognized)
+                .map(|candidate| candidate.name.clone())
+                .collect(),
+            unrecognized_mod_hash: recognition
+                .unrecognized_curried
+                .as_ref()
+                .map(|curried| curried.mod_hash.clone()),
+        }
+    }
+}
+
+/// Snapshots the structure-only recognition of a puzzle reveal.
+pub fn recognition_snapshot(puzzle_reveal_bytes: &[u8]) -> RecognitionSnapshot {
+    RecognitionSnapshot::from(&recognize_puzzle_structure(puzzle_reveal_bytes))
+}
+
+/// Describes, one line per change, how `after` recognizes the puzzle
+/// differently from `before`. Empty when the two agree.
+pub fn diff_recognition_snapshots(
+    before: &RecognitionSnapshot,
+    after: &RecognitionSnapshot,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    for depth in 0..before.layers.len().max(after.layers.len()) {
+        match (before.layers.get(depth), after.layers.get(depth)) {
+            (Some(old), Some(new)) if old.name != new.name => {
+                changes.push(format!("layer {depth}: {} is now {}", old.name, new.name));
+            }
+            (Some(old), Some(new)) => {
+                if old.mod_hash != new.mod_hash {
+                    changes.push(format!(
+                        "layer {depth} ({}): mod_hash {} is now {}",
+                        old.name, old.mod_hash, new.mod_hash
+                    ));
+                }
+                diff_params(depth, &old.name, &old.params, &new.params, &mut changes);
+            }
+            (Some(old), None) => {
+                changes.push(format!("layer {depth} ({}) is no longer recognized", old.name));
+            }
+            (None, Some(new)) => {
+                changes.push(format!("layer {depth} ({}) is newly recognized", new.name));
+            }
+            (None, None) => {}
+        }
+    }
+    if before.ambiguous != after.ambiguous {
+        changes.push(format!(
+            "ambiguous matches {:?} are now {:?}",
+            before.ambiguous, after.ambiguous
+        ));
+    }
+    if before.unrecognized_mod_hash != after.unrecognized_mod_hash {
+        changes.push(format!(
+            "unrecognized mod_hash {} is now {}",
+            before.unrecognized_mod_hash.as_deref().unwrap_or("none"),
+            after.unrecognized_mod_hash.as_deref().unwrap_or("none")
+        ));
+    }
+    changes
+}
+
+fn diff_params(depth: usize, layer: &str, old: &Value, new: &Value, changes: &mut Vec<String>) {
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        if old != new {
+            changes.push(format!("layer {depth} ({layer}): params {old} are now {new}"));
+        }
+        return;
+    };
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for key in keys {
+        let (was, is) = (old.get(key), new.get(key));
+        if was != is {
+            let show = |value: Option<&Value>| value.map_or("missing".to_string(), Value::to_string);
+            changes.push(format!(
+                "layer {depth} ({layer}): param {key} {} is now {}",
+                show(was),
+                show(is)
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(asset_id: &str, inner: &str) -> RecognitionSnapshot {
+        serde_json::from_value(json!({
+            "layers": [
+                { "name": "cat_layer", "mod_hash": "0xca", "params": { "asset_id": asset_id } },
+                { "name": inner, "mod_hash": "0xe9", "params": {} },
+            ],
+        }))
+        .expect("snapshot")
+    }
+
+    #[test]
+    fn reports_param_and_layer_changes() {
+        let before = snapshot("0xaa", "standard_layer");
+        assert!(diff_recognition_snapshots(&before, &before.clone()).is_empty());
+
+        let mut after = snapshot("0xbb", "p2_delegated_puzzle_layer");
+        assert_eq!(
+            diff_recognition_snapshots(&before, &after),
+            vec![
+                "layer 0 (cat_layer): param asset_id \"0xaa\" is now \"0xbb\"",
+                "layer 1: standard_layer is now p2_delegated_puzzle_layer",
+            ]
+        );
+
+        after.layers.truncate(1);
+        after.unrecognized_mod_hash = Some("0xe9".to_string());
+        assert_eq!(
+            diff_recognition_snapshots(&before, &after)[1..],
+            [
+                "layer 1 (standard_layer) is no longer recognized",
+                "unrecognized mod_hash none is now 0xe9",
+            ]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::schema::{InputInfo, NetworkInfo, ResultInfo, SpendAnalysis, ToolInfo};
+
+/// Writes an inspection document incrementally: the header up front, each
+/// spend as it is analyzed, and the bundle-level result fields last.
+///
+/// The document deserializes to the same `InspectionOutput` as the buffered
+/// form; only key order differs (`result.spends` comes first in `result`).
+pub(crate) struct OutputStream<'w> {
+    writer: &'w mut dyn Write,
+    spends_written: usize,
+}
+
+impl<'w> OutputStream<'w> {
+    pub(crate) fn begin(
+        writer: &'w mut dyn Write,
+        schema_version: &str,
+        tool: &ToolInfo,
+        network: &NetworkInfo,
+        input: &InputInfo,
+    ) -> Result<Self> {
+        write!(
+            writer,
+            "{{\"schema_version\":{},\"tool\":{},\"network\":{},\"input\":{},\"result\":{{\"spends\":[",
+            json(&schema_version)?,
+            json(tool)?,
+            json(network)?,
+            json(input)?,
+        )?;
+        Ok(Self {
+            writer,
+            spends_written: 0,
+        })
+    }
+
+    pub(crate) fn spend(&mut self, spend: &SpendAnalysis) -> Result<()> {
+        if self.spends_written > 0 {
+            self.writer.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut *self.writer, spend)?;
+        self.writer.flush()?;
+        self.spends_written += 1;
+        Ok(())
+    }
+
+    /// Closes the spend array and writes every other `result` field.
+    pub(crate) fn finish(self, result: &ResultInfo) -> Result<()> {
+        let Value::Object(mut fields) = serde_json::to_value(result)? else {
+            unreachable!("ResultInfo serializes to an object");
+        };
+        fields.remove("spends");
+        self.writer.write_all(b"]")?;
+        for (key, value) in fields {
+            write!(self.writer, ",{}:{}", json(&key)?, json(&value)?)?;
+        }
+        self.writer.write_all(b"}}\n")?;
+        self.writer.flush().context("failed to flush streamed output")
+    }
+}
+
+fn json(value: &impl Serialize) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
@@ -0,0 +1,59 @@
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::schema::SpendAnalysis;
+
+impl SpendAnalysis {
+    /// Renders this spend as indented plain text: the coin, evaluation
+    /// status, recognized layer stack with params, and emitted conditions.
+    /// Suited to log lines and detail panes that show one spend at a time.
+    pub fn render_text(&self) -> String {
+        let coin = &self.coin_spend.coin;
+        let evaluation = &self.evaluation;
+        let mut text = format!("coin {} ({} mojos)\n", coin.coin_id, coin.amount);
+        let _ = writeln!(text, "  parent:      {}", coin.parent_coin_id);
+        let _ = writeln!(text, "  puzzle hash: {}", coin.puzzle_hash);
+        let _ = writeln!(text, "  evaluation:  {} (cost {})", evaluation.status, evaluation.cost);
+        if let Some(failure) = &evaluation.failure {
+            let _ = writeln!(text, "  failure:     {}: {}", failure.kind, failure.message);
+        }
+        if let Some(role) = &self.puzzle_behavior.explanation.role {
+            let _ = writeln!(text, "  role:        {role}");
+        }
+
+        text.push_str("  layers:\n");
+        if self.puzzle.recognition.wrappers.is_empty() {
+            text.push_str("    (unrecognized)\n");
+        }
+        for (depth, wrapper) in self.puzzle.recognition.wrappers.iter().enumerate() {
+            let _ = write!(text, "    {}{}", "  ".repeat(depth), wrapper.name);
+            if let Value::Object(params) = &wrapper.params {
+                for (key, value) in params {
+                    let _ = write!(text, " {key}={}", plain(value));
+                }
+            }
+            text.push('\n');
+        }
+
+        text.push_str("  conditions:\n");
+        if evaluation.conditions.is_empty() {
+            text.push_str("    (none)\n");
+        }
+        for condition in &evaluation.conditions {
+            let _ = write!(text, "    {}", condition.opcode);
+            for arg in &condition.args {
+                let _ = write!(text, " {}", plain(arg));
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
@@ -6,8 +6,13 @@ pub fn strip_0x(s: &str) -> &str {
         .unwrap_or(s)
 }
 
+/// Decodes hex with an optional `0x` prefix. ASCII whitespace anywhere in the
+/// string is ignored, so hex copied from wrapped logs decodes as-is.
 pub fn decode_hex(s: &str) -> Result<Vec<u8>> {
-    let raw = strip_0x(s).trim();
+    let raw = strip_0x(s.trim())
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
     if raw.is_empty() {
         return Ok(Vec::new());
     }
@@ -21,6 +26,13 @@ pub fn encode_hex_prefixed(bytes: &[u8]) -> String {
     format!("0x{}", hex::encode(bytes))
 }
 
+/// Reads memo bytes as text when they are non-empty UTF-8 without control
+/// characters, which is how wallets store human-readable memos.
+pub fn printable_memo_text(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    (!text.is_empty() && !text.chars().any(char::is_control)).then(|| text.to_string())
+}
+
 pub fn normalize_hex_no_prefix(s: &str) -> Result<String> {
     let bytes = decode_hex(s)?;
     Ok(hex::encode(bytes))
@@ -32,3 +44,24 @@ pub fn read_text_input(path_or_stdin: &str, stdin_fallback: Option<String>) -> R
     }
     Ok(std::fs::read_to_string(path_or_stdin)?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_hex_ignores_internal_whitespace() {
+        assert_eq!(decode_hex(" 0xff01\n02 03\t\n").unwrap(), vec![0xff, 0x01, 0x02, 0x03]);
+        assert_eq!(normalize_hex_no_prefix("ab\r\ncd").unwrap(), "abcd");
+        assert!(decode_hex("0xabc").is_err());
+        assert!(decode_hex("0xzz").is_err());
+    }
+
+    #[test]
+    fn printable_memo_text_rejects_binary() {
+        assert_eq!(printable_memo_text(b"gm frens").as_deref(), Some("gm frens"));
+        assert!(printable_memo_text(&[0xff; 32]).is_none());
+        assert!(printable_memo_text(b"a\nb").is_none());
+        assert!(printable_memo_text(b"").is_none());
+    }
+}
@@ -1,6 +1,20 @@
-use chia_inspect_core::{ExplainLevel, inspect_bundle, load_mempool_blob_input};
-use chia_protocol::{Coin, CoinSpend, Program, SpendBundle};
-use clvm_utils::tree_hash_from_bytes;
+use chia_bls::SecretKey;
+use chia_inspect_core::events::output_events;
+use chia_inspect_core::markdown::render_markdown;
+use chia_inspect_core::network::{Network, constants_from_json};
+use chia_inspect_core::readiness::verdict;
+use chia_inspect_core::recognize::{SOURCE_REF, SOURCE_REPO};
+use chia_inspect_core::schema::{InspectionOutput, SCHEMA_VERSION, validate_output_document};
+use chia_inspect_core::{
+    ExplainLevel, InputSource, InspectConfig, analyze_spends, inspect_bundle, inspect_bundle_streaming,
+    inspect_bundle_with_config, load_block_spends_input, load_mempool_blob_input,
+};
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program, SpendBundle};
+use chia_puzzle_types::standard::StandardSolution;
+use chia_sdk_driver::{Layer, SpendContext, StandardLayer};
+use clvm_utils::{tree_hash, tree_hash_from_bytes};
+use clvmr::NodePtr;
+use clvmr::serde::node_to_bytes;
 use serde_json::{Value, json};
 
 fn normalize_tool_version(value: &mut Value) {
@@ -21,6 +35,11 @@ fn sample_spend_bundle() -> SpendBundle {
     SpendBundle::new(vec![spend], Default::default())
 }
 
+fn inspect_sample(config: &InspectConfig) -> InspectionOutput {
+    inspect_bundle_with_config(InputSource::Mempool(None), sample_spend_bundle(), Vec::new(), config)
+        .expect("inspect")
+}
+
 #[test]
 fn golden_simple_mempool_blob() {
     let bundle = sample_spend_bundle();
@@ -38,3 +57,352 @@ fn golden_simple_mempool_blob() {
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn streamed_output_matches_fixture() {
+    let mut streamed = Vec::new();
+    inspect_bundle_streaming(
+        InputSource::Mempool(None),
+        sample_spend_bundle(),
+        Vec::new(),
+        &InspectConfig::default(),
+        &mut streamed,
+    )
+    .expect("stream");
+    let mut actual: Value = serde_json::from_slice(&streamed).expect("streamed json");
+    let mut expected: Value =
+        serde_json::from_str(include_str!("fixtures/simple_inspection.json")).expect("load fixture");
+    normalize_tool_version(&mut actual);
+    normalize_tool_version(&mut expected);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn digest_tracks_semantics_not_tool_version() {
+    let mut output = validate_output_document(include_str!("fixtures/simple_inspection.json"))
+        .expect("fixture validates");
+    let digest = output.result.digest.clone();
+    assert_eq!(output.semantic_digest(), digest);
+
+    output.tool.version = "0.0.0".to_string();
+    assert_eq!(output.semantic_digest(), digest);
+
+    output.result.summary.fee_mojos += 1;
+    assert_ne!(output.semantic_digest(), digest);
+}
+
+#[test]
+fn analyze_spends_needs_no_bundle() {
+    let spends = sample_spend_bundle().coin_spends;
+    let output = analyze_spends(&spends, Network::Testnet11).expect("analyze");
+    assert_eq!(output.result.status, "ok");
+    assert_eq!(output.network.name, "testnet11");
+    assert_eq!(output.result.summary.fee_mojos, 0);
+    assert_eq!(output.result.spends[0].evaluation.additions.len(), 1);
+    assert!(output.result.signatures.is_default);
+}
+
+#[test]
+fn fee_per_cost_blocks_underpaying_bundle() {
+    let output = inspect_sample(&InspectConfig {
+        fee_per_cost: Some(1),
+        ..Default::default()
+    });
+    let readiness = &output.result.readiness;
+    assert_eq!(readiness.min_fee_mojos, Some(output.result.summary.cost.total_cost));
+    assert!(!readiness.submittable);
+    assert!(readiness.blockers.iter().any(|blocker| blocker.kind == "fee_below_minimum"));
+
+    let output = inspect_sample(&InspectConfig {
+        fee_per_cost: Some(0),
+        ..Default::default()
+    });
+    assert_eq!(output.result.readiness.min_fee_mojos, Some(0));
+    assert!(output.result.readiness.submittable);
+}
+
+#[test]
+fn verdict_reduces_a_real_inspection() {
+    let output = inspect_sample(&InspectConfig {
+        fee_per_cost: Some(1),
+        ..Default::default()
+    });
+    let verdict = verdict(&output);
+    assert_eq!(verdict.bundle_id, output.result.bundle_id);
+    assert_eq!(verdict.status, "ok");
+    assert!(!verdict.submittable);
+    assert_eq!(verdict.fee_mojos, 0);
+    assert_eq!(verdict.blockers.len(), output.result.readiness.blockers.len());
+    assert!(verdict.blockers.iter().any(|blocker| blocker.kind == "fee_below_minimum"));
+}
+
+#[test]
+fn inspected_spend_renders_as_text() {
+    let output = inspect_sample(&InspectConfig::default());
+    let spend = &output.result.spends[0];
+    let text = spend.render_text();
+    assert!(text.starts_with(&format!("coin {} (1 mojos)\n", spend.coin_spend.coin.coin_id)));
+    assert!(text.contains("    (unrecognized)\n"));
+    assert!(text.contains("  evaluation:  ok (cost "));
+    assert!(text.contains(&format!("\n    CREATE_COIN 0x{} 1\n", "22".repeat(32))));
+}
+
+#[test]
+fn inspected_bundle_renders_as_markdown() {
+    let output = inspect_sample(&InspectConfig::default());
+    let md = render_markdown(&output);
+    assert!(md.starts_with("# Spend bundle inspection\n"));
+    assert!(md.contains(&format!("| Bundle id | `{}` |", output.result.bundle_id)));
+    assert!(md.contains("| Submittable | yes |"));
+    assert!(md.contains(&format!("| `0x{}` | +1 |", "22".repeat(32))));
+    assert!(md.contains("## Spend #0"));
+    assert!(md.contains("- unrecognized\n"));
+    assert!(md.contains(&format!("- CREATE_COIN `0x{}` `1`\n", "22".repeat(32))));
+    assert!(!md.contains("[!WARNING]"));
+
+    let blocked = render_markdown(&inspect_sample(&InspectConfig {
+        fee_per_cost: Some(1),
+        ..Default::default()
+    }));
+    assert!(blocked.contains("| Submittable | no |"));
+    assert!(blocked.contains("> - **blocker `fee_below_minimum`**: "));
+}
+
+#[test]
+fn inspected_bundle_flattens_to_events() {
+    let output = inspect_sample(&InspectConfig {
+        fee_per_cost: Some(1),
+        ..Default::default()
+    });
+    let events = output_events(&output);
+    let condition = events
+        .iter()
+        .find(|event| event["type"] == "condition")
+        .expect("condition event");
+    assert_eq!(condition["opcode"], "CREATE_COIN");
+    assert_eq!(condition["spend_index"], 0);
+    assert_eq!(condition["bundle_id"], json!(output.result.bundle_id));
+    let blocker = events
+        .iter()
+        .find(|event| event["type"] == "readiness_blocker")
+        .expect("blocker event");
+    assert_eq!(blocker["kind"], "fee_below_minimum");
+    assert_eq!(blocker["spend_index"], Value::Null);
+}
+
+#[test]
+fn forbidden_condition_fails_inspected_bundle() {
+    let output = inspect_sample(&InspectConfig {
+        forbidden_conditions: vec!["AGG_SIG_UNSAFE".to_string()],
+        ..Default::default()
+    });
+    assert_eq!(output.result.status, "ok");
+
+    let output = inspect_sample(&InspectConfig {
+        forbidden_conditions: vec!["create_coin".to_string()],
+        ..Default::default()
+    });
+    assert_eq!(output.result.status, "failed");
+    let error = output.result.error.as_ref().expect("error");
+    assert_eq!(error.kind, "forbidden_condition");
+    assert_eq!(output.result.spends[0].evaluation.status, "failed");
+    let pointer = error.pointer.as_deref().expect("pointer");
+    assert_eq!(pointer, "/result/spends/0/evaluation/failure");
+    let document = serde_json::to_value(&output).expect("serialize");
+    assert_eq!(document.pointer(pointer).expect("resolves")["kind"], "forbidden_condition");
+    assert!(!output.result.readiness.submittable);
+}
+
+#[test]
+fn high_cost_threshold_warns_on_inspected_spend() {
+    let warned = |threshold: u64| {
+        inspect_sample(&InspectConfig {
+            high_cost_threshold: Some(threshold),
+            ..Default::default()
+        })
+        .result
+        .warnings
+        .iter()
+        .any(|warning| warning.code == "HIGH_COST_SPEND")
+    };
+    assert!(warned(1));
+    assert!(!warned(u64::MAX));
+}
+
+#[test]
+fn coverage_reports_unrecognized_spend() {
+    assert!(inspect_sample(&InspectConfig::default()).result.coverage.is_none());
+    let output = inspect_sample(&InspectConfig {
+        coverage: true,
+        ..Default::default()
+    });
+    let coverage = output.result.coverage.expect("coverage");
+    assert_eq!(coverage.spends, 1);
+    assert_eq!(coverage.unrecognized, 1);
+    assert!(coverage.unrecognized_puzzle_hashes.contains(&output.result.spends[0].coin_spend.coin.puzzle_hash));
+}
+
+#[test]
+fn reported_additions_are_cross_checked() {
+    let blob = json!({ "spend_bundle": sample_spend_bundle(), "additions": [] });
+    let (source, parsed, notes) = load_mempool_blob_input(&blob.to_string()).expect("parse blob");
+    let output = inspect_bundle(source, parsed, notes, ExplainLevel::Deep).expect("inspect");
+    let mismatch = output
+        .result
+        .warnings
+        .iter()
+        .find(|warning| warning.code == "RPC_COIN_MISMATCH")
+        .expect("mismatch warning");
+    let details = mismatch.details.as_ref().expect("details");
+    assert_eq!(details["field"], "additions");
+    assert_eq!(details["only_computed"].as_array().expect("only_computed").len(), 1);
+}
+
+#[test]
+fn max_disasm_len_truncates_inspected_disassembly() {
+    let output = inspect_sample(&InspectConfig {
+        max_disasm_len: Some(4),
+        ..Default::default()
+    });
+    let puzzle = &output.result.spends[0].puzzle;
+    assert!(puzzle.solution_disasm.starts_with("((g1… (truncated, "));
+    assert_eq!(puzzle.puzzle_reveal_disasm, "1");
+}
+
+#[test]
+fn custom_constants_validate_the_bundle() {
+    let json = format!(r#"{{"genesis_challenge": "{}", "cost_per_byte": 0}}"#, "37".repeat(32));
+    let (constants, ignored) = constants_from_json(&json).expect("constants");
+    assert!(ignored.is_empty());
+    let output = inspect_sample(&InspectConfig {
+        constants: Some(constants),
+        ..Default::default()
+    });
+    assert_eq!(output.result.status, "ok");
+    assert_eq!(output.network.name, "custom");
+    assert_eq!(output.network.genesis_challenge, Some(format!("0x{}", "37".repeat(32))));
+    assert_eq!(output.result.summary.cost.byte_cost, 0);
+}
+
+#[test]
+fn source_override_restamps_sdk_layers() {
+    let mut ctx = SpendContext::new();
+    let layer = StandardLayer::new(SecretKey::from_seed(&[1; 32]).public_key());
+    let puzzle = layer.construct_puzzle(&mut ctx).expect("puzzle");
+    let solution = layer
+        .construct_solution(
+            &mut ctx,
+            StandardSolution {
+                original_public_key: None,
+                delegated_puzzle: NodePtr::NIL,
+                solution: NodePtr::NIL,
+            },
+        )
+        .expect("solution");
+    let coin = Coin::new(Bytes32::new([0x11; 32]), Bytes32::new(tree_hash(&ctx, puzzle).to_bytes()), 1);
+    let spend = CoinSpend::new(
+        coin,
+        Program::from(node_to_bytes(&ctx, puzzle).expect("puzzle bytes")),
+        Program::from(node_to_bytes(&ctx, solution).expect("solution bytes")),
+    );
+    let inspect = |config: &InspectConfig| {
+        let bundle = SpendBundle::new(vec![spend.clone()], Default::default());
+        let output = inspect_bundle_with_config(InputSource::Coin, bundle, Vec::new(), config)
+            .expect("inspect");
+        output.result.spends[0].puzzle.recognition.wrappers[0].clone()
+    };
+
+    let wrapper = inspect(&InspectConfig::default());
+    assert_eq!(wrapper.name, "standard_layer");
+    assert_eq!(wrapper.source_repo, SOURCE_REPO);
+    assert_eq!(wrapper.source_ref, SOURCE_REF);
+
+    let wrapper = inspect(&InspectConfig {
+        source_repo: Some("example/chia-wallet-sdk".to_string()),
+        source_ref: Some("patched".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(wrapper.source_repo, "example/chia-wallet-sdk");
+    assert_eq!(wrapper.source_ref, "patched");
+}
+
+#[test]
+fn dump_consensus_attaches_raw_conditions() {
+    let config = InspectConfig {
+        dump_consensus: true,
+        ..Default::default()
+    };
+    let output = inspect_bundle_with_config(
+        InputSource::Mempool(None),
+        sample_spend_bundle(),
+        Vec::new(),
+        &config,
+    )
+    .expect("inspect");
+    let consensus = &output.result.debug.expect("debug")["consensus"];
+    assert_eq!(consensus["removal_amount"], "1");
+    assert_eq!(consensus["spends"][0]["coin_amount"], 1);
+    assert_eq!(
+        consensus["spends"][0]["create_coin"][0],
+        json!([format!("0x{}", "22".repeat(32)), 1, null])
+    );
+}
+
+#[test]
+fn empty_block_warns_or_fails_under_strict() {
+    let inspect = |strict: bool| {
+        let (source, bundle, notes) = load_block_spends_input("[]").expect("load");
+        let config = InspectConfig {
+            strict,
+            ..Default::default()
+        };
+        inspect_bundle_with_config(source, bundle, notes, &config).expect("inspect")
+    };
+
+    let lenient = inspect(false);
+    assert_eq!(lenient.result.status, "ok");
+    assert!(lenient.result.warnings.iter().any(|warning| warning.code == "EMPTY_BUNDLE"));
+
+    let strict = inspect(true);
+    assert_eq!(strict.result.status, "failed");
+    assert_eq!(strict.result.error.expect("error").kind, "empty_bundle");
+    assert!(!strict.result.readiness.submittable);
+}
+
+#[test]
+fn conditions_of_yields_spend_indexed_matches() {
+    let output = inspect_bundle(
+        InputSource::Mempool(None),
+        sample_spend_bundle(),
+        Vec::new(),
+        ExplainLevel::Deep,
+    )
+    .expect("inspect");
+    let create_coins = output.conditions_of("CREATE_COIN").collect::<Vec<_>>();
+    assert_eq!(create_coins.len(), 1);
+    assert_eq!(create_coins[0].0, 0);
+    assert_eq!(create_coins[0].1.args[0], json!(format!("0x{}", "22".repeat(32))));
+    assert_eq!(output.conditions_of("RESERVE_FEE").count(), 0);
+}
+
+#[test]
+fn fixture_validates_against_current_schema() {
+    let output = validate_output_document(include_str!("fixtures/simple_inspection.json"))
+        .expect("fixture validates");
+    assert_eq!(output.schema_version, SCHEMA_VERSION);
+
+    let mut unsupported: Value =
+        serde_json::from_str(include_str!("fixtures/simple_inspection.json")).expect("load fixture");
+    unsupported["schema_version"] = json!("chia.inspect.spendbundle.v1");
+    assert!(validate_output_document(&unsupported.to_string()).is_err());
+}
+
+#[test]
+fn first_v2_documents_still_validate() {
+    // Written before readiness, warnings and the other later result fields.
+    let output = validate_output_document(include_str!("fixtures/v2_initial_inspection.json"))
+        .expect("initial v2 document validates");
+    assert!(!output.result.readiness.submittable);
+    assert!(output.result.warnings.is_empty());
+    assert!(output.result.bundle_id.is_empty());
+}
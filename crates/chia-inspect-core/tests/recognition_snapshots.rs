@@ -0,0 +1,69 @@
+//! Recognizer-level golden harness: puzzles built with the pinned SDK must
+//! still be recognized as the snapshots in `fixtures/recognition_snapshots.json`
+//! say. After an SDK bump, a failure lists what changed; once the change is
+//! understood, rerun with `UPDATE_RECOGNITION_SNAPSHOTS=1` to rewrite the file.
+
+use std::collections::BTreeMap;
+
+use chia_bls::PublicKey;
+use chia_inspect_core::snapshot::{
+    RecognitionSnapshot, diff_recognition_snapshots, recognition_snapshot,
+};
+use chia_protocol::Bytes32;
+use chia_puzzles::SINGLETON_LAUNCHER;
+use chia_sdk_driver::{CatLayer, Layer, SpendContext, StandardLayer};
+use clvmr::serde::{node_from_bytes, node_to_bytes};
+
+const FIXTURE: &str = "tests/fixtures/recognition_snapshots.json";
+
+fn puzzle_reveals() -> BTreeMap<&'static str, Vec<u8>> {
+    let mut ctx = SpendContext::new();
+    let standard = StandardLayer::new(PublicKey::default());
+    let cat = CatLayer::new(Bytes32::new([7; 32]), StandardLayer::new(PublicKey::default()));
+    let puzzles = [
+        ("standard", standard.construct_puzzle(&mut ctx).expect("standard")),
+        ("cat_standard", cat.construct_puzzle(&mut ctx).expect("cat")),
+        (
+            "singleton_launcher",
+            node_from_bytes(&mut ctx, &SINGLETON_LAUNCHER).expect("launcher"),
+        ),
+    ];
+    puzzles
+        .into_iter()
+        .map(|(name, ptr)| (name, node_to_bytes(&ctx, ptr).expect("node bytes")))
+        .collect()
+}
+
+#[test]
+fn recognition_matches_snapshots() {
+    let actual: BTreeMap<String, RecognitionSnapshot> = puzzle_reveals()
+        .into_iter()
+        .map(|(name, reveal)| (name.to_string(), recognition_snapshot(&reveal)))
+        .collect();
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURE);
+    if std::env::var_os("UPDATE_RECOGNITION_SNAPSHOTS").is_some() {
+        let json = serde_json::to_string_pretty(&actual).expect("snapshot json");
+        std::fs::write(&path, json + "\n").expect("write snapshots");
+        return;
+    }
+
+    let expected: BTreeMap<String, RecognitionSnapshot> =
+        serde_json::from_str(include_str!("fixtures/recognition_snapshots.json")).expect("load fixture");
+    let mut changes = Vec::new();
+    for (name, snapshot) in &actual {
+        match expected.get(name) {
+            Some(before) => changes.extend(
+                diff_recognition_snapshots(before, snapshot)
+                    .into_iter()
+                    .map(|change| format!("{name}: {change}")),
+            ),
+            None => changes.push(format!("{name}: no snapshot")),
+        }
+    }
+    assert!(
+        changes.is_empty(),
+        "recognition changed; rerun with UPDATE_RECOGNITION_SNAPSHOTS=1 once reviewed:\n{}",
+        changes.join("\n")
+    );
+}
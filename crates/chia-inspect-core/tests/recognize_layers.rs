@@ -1,19 +1,34 @@
 use chia_bls::PublicKey;
-use chia_inspect_core::recognize::recognize_puzzle_and_solution;
+use chia_inspect_core::Recognizer;
+use chia_inspect_core::recognize::{
+    RecognizeOptions, recognize_puzzle_and_solution, recognize_with_options,
+    try_recognize_puzzle_and_solution, used_escape_path,
+};
 use chia_protocol::{Bytes32, Coin};
 use chia_puzzle_types::{
     CoinProof, EveProof, Proof,
-    cat::CatSolution,
+    cat::{CatArgs, CatSolution},
+    Memos,
     did::DidSolution,
     nft::{NftOwnershipLayerSolution, NftStateLayerSolution},
+    offer::{NotarizedPayment, Payment, SettlementPaymentsSolution},
     singleton::SingletonSolution,
     standard::StandardSolution,
 };
+use chia_puzzles::{
+    CAT_PUZZLE, EVERYTHING_WITH_SIGNATURE, GENESIS_BY_COIN_ID, P2_DELEGATED_PUZZLE,
+    SINGLETON_LAUNCHER,
+};
 use chia_sdk_driver::{
-    CatLayer, DidLayer, Layer, NftOwnershipLayer, NftStateLayer, RoyaltyTransferLayer,
-    SingletonLayer, SpendContext, StandardLayer,
+    CatLayer, DidLayer, Layer, NftOwnershipLayer, NftStateLayer, RevocationLayer,
+    RoyaltyTransferLayer, SettlementLayer, SingletonLayer, SpendContext, StandardLayer,
+};
+use clvm_traits::clvm_curried_args;
+use clvm_utils::{CurriedProgram, tree_hash};
+use clvmr::{
+    NodePtr,
+    serde::{node_from_bytes, node_to_bytes},
 };
-use clvmr::{NodePtr, serde::node_to_bytes};
 
 fn node_bytes(ctx: &SpendContext, ptr: NodePtr) -> Vec<u8> {
     node_to_bytes(ctx, ptr).expect("node bytes")
@@ -47,6 +62,154 @@ fn recognizes_standard_layer() {
     assert!(recognition.recognized);
     assert_eq!(wrapper_names(&recognition), vec!["standard_layer"]);
     assert!(recognition.parsed_solution.is_some());
+    assert!(!used_escape_path(&recognition));
+}
+
+#[test]
+fn standard_hidden_puzzle_spend_uses_escape_path() {
+    let mut ctx = SpendContext::new();
+    let layer = StandardLayer::new(PublicKey::default());
+    let puzzle = layer.construct_puzzle(&mut ctx).expect("construct puzzle");
+    let solution = layer
+        .construct_solution(
+            &mut ctx,
+            StandardSolution {
+                original_public_key: Some(PublicKey::default()),
+                delegated_puzzle: NodePtr::NIL,
+                solution: NodePtr::NIL,
+            },
+        )
+        .expect("construct solution");
+
+    let recognition = recognize_puzzle_and_solution(&node_bytes(&ctx, puzzle), &node_bytes(&ctx, solution));
+    assert!(used_escape_path(&recognition));
+}
+
+#[test]
+fn recognizer_reuses_known_layer_stack() {
+    let mut ctx = SpendContext::new();
+    let layer = StandardLayer::new(PublicKey::default());
+    let puzzle = layer.construct_puzzle(&mut ctx).expect("construct puzzle");
+    let solution = layer
+        .construct_solution(
+            &mut ctx,
+            StandardSolution {
+                original_public_key: None,
+                delegated_puzzle: NodePtr::NIL,
+                solution: NodePtr::NIL,
+            },
+        )
+        .expect("construct solution");
+    let (puzzle, solution) = (node_bytes(&ctx, puzzle), node_bytes(&ctx, solution));
+
+    let mut recognizer = Recognizer::new();
+    let first = recognizer.recognize(&puzzle, &solution);
+    let second = recognizer.recognize(&puzzle, &solution);
+    assert_eq!(recognizer.len(), 1);
+    let expected = serde_json::to_value(recognize_puzzle_and_solution(&puzzle, &solution))
+        .expect("serialize");
+    assert_eq!(serde_json::to_value(first).expect("serialize"), expected);
+    assert_eq!(serde_json::to_value(second).expect("serialize"), expected);
+}
+
+#[test]
+fn recognizer_follows_each_revocation_path() {
+    let mut ctx = SpendContext::new();
+    let standard = StandardLayer::new(PublicKey::default());
+    let inner_puzzle = standard.construct_puzzle(&mut ctx).expect("inner puzzle");
+    let standard_solution = standard
+        .construct_solution(
+            &mut ctx,
+            StandardSolution {
+                original_public_key: None,
+                delegated_puzzle: NodePtr::NIL,
+                solution: NodePtr::NIL,
+            },
+        )
+        .expect("standard solution");
+    let hidden_puzzle = ctx.alloc(&1_u8).expect("hidden puzzle");
+    let layer = RevocationLayer::new(
+        Bytes32::new(tree_hash(&ctx, hidden_puzzle).to_bytes()),
+        Bytes32::new(tree_hash(&ctx, inner_puzzle).to_bytes()),
+    );
+    let puzzle = layer.construct_puzzle(&mut ctx).expect("construct puzzle");
+    let hidden_solution = ctx
+        .alloc(&(true, (hidden_puzzle, (NodePtr::NIL, ()))))
+        .expect("hidden solution");
+    let inner_solution = ctx
+        .alloc(&(false, (inner_puzzle, (standard_solution, ()))))
+        .expect("inner solution");
+    let puzzle = node_bytes(&ctx, puzzle);
+
+    // The hidden path stops at the revocation layer; the inner path of the
+    // same puzzle hash must still descend into the standard layer.
+    let mut recognizer = Recognizer::new();
+    for solution in [hidden_solution, inner_solution, hidden_solution] {
+        let solution = node_bytes(&ctx, solution);
+        let recognition = recognizer.recognize(&puzzle, &solution);
+        assert_eq!(
+            serde_json::to_value(recognition).expect("serialize"),
+            serde_json::to_value(recognize_puzzle_and_solution(&puzzle, &solution))
+                .expect("serialize")
+        );
+    }
+    assert_eq!(recognizer.len(), 1);
+    let inner = recognizer.recognize(&puzzle, &node_bytes(&ctx, inner_solution));
+    assert_eq!(wrapper_names(&inner), vec!["revocation_layer", "standard_layer"]);
+}
+
+#[test]
+fn strict_recognition_errors_on_undecodable_puzzle() {
+    assert!(try_recognize_puzzle_and_solution(&[0xff], &[0x80]).is_err());
+    let lenient = recognize_puzzle_and_solution(&[0xff], &[0x80]);
+    assert!(!lenient.recognized);
+    assert!(lenient.parsed_solution.is_some());
+    assert!(try_recognize_puzzle_and_solution(&[0x01], &[0x80]).is_ok());
+}
+
+#[test]
+fn skip_solutions_recognizes_structure_only() {
+    let mut ctx = SpendContext::new();
+    let layer = StandardLayer::new(PublicKey::default());
+    let puzzle = layer.construct_puzzle(&mut ctx).expect("construct puzzle");
+
+    // The solution is not even decoded, so garbage bytes are fine.
+    let recognition = recognize_with_options(
+        &node_bytes(&ctx, puzzle),
+        &[0xff],
+        &RecognizeOptions { skip_solutions: true, ..Default::default() },
+    );
+    assert!(recognition.recognized);
+    assert_eq!(wrapper_names(&recognition), vec!["standard_layer"]);
+    assert!(recognition.parsed_solution.is_none());
+}
+
+#[test]
+fn compact_recognition_drops_parsed_debug() {
+    let mut ctx = SpendContext::new();
+    let layer = StandardLayer::new(PublicKey::default());
+    let puzzle = layer.construct_puzzle(&mut ctx).expect("construct puzzle");
+    let solution = layer
+        .construct_solution(
+            &mut ctx,
+            StandardSolution {
+                original_public_key: None,
+                delegated_puzzle: NodePtr::NIL,
+                solution: NodePtr::NIL,
+            },
+        )
+        .expect("construct solution");
+    let (puzzle, solution) = (node_bytes(&ctx, puzzle), node_bytes(&ctx, solution));
+
+    let layer_result = |options: &RecognizeOptions| {
+        let recognition = recognize_with_options(&puzzle, &solution, options);
+        recognition.parsed_solution.expect("parsed solution")["layers"][0]["result"].clone()
+    };
+    let full = layer_result(&RecognizeOptions::default());
+    assert!(full.get("parsed_debug").is_some());
+    let compact = layer_result(&RecognizeOptions { compact: true, ..Default::default() });
+    assert!(compact.get("parsed_debug").is_none());
+    assert_eq!(compact["status"], full["status"]);
 }
 
 #[test]
@@ -86,6 +249,82 @@ fn recognizes_cat_then_standard_layers() {
     );
 }
 
+/// Recognizes a spend of the CAT that `tail` issues, revealing the TAIL to
+/// melt one mojo.
+fn recognize_tail_reveal(
+    ctx: &mut SpendContext,
+    tail: NodePtr,
+) -> chia_inspect_core::schema::PuzzleRecognition {
+    let asset_id = Bytes32::new(tree_hash(ctx, tail).to_bytes());
+
+    // The `1` inner puzzle returns its solution, so the solution is the
+    // condition list carrying the magic TAIL-reveal CREATE_COIN.
+    let cat_mod = node_from_bytes(ctx, &CAT_PUZZLE).expect("cat mod");
+    let inner_puzzle = ctx.alloc(&1_u8).expect("inner puzzle");
+    let puzzle = ctx
+        .alloc(&CurriedProgram {
+            program: cat_mod,
+            args: CatArgs::new(asset_id, inner_puzzle),
+        })
+        .expect("construct puzzle");
+    let conditions = ctx
+        .alloc(&[(51_u8, (NodePtr::NIL, (-113_i64, (tail, (NodePtr::NIL, ())))))])
+        .expect("conditions");
+    let solution = ctx
+        .alloc(&CatSolution {
+            inner_puzzle_solution: conditions,
+            lineage_proof: None,
+            prev_coin_id: Bytes32::new([1; 32]),
+            this_coin_info: Coin::new(Bytes32::new([2; 32]), Bytes32::new([3; 32]), 1),
+            next_coin_proof: CoinProof {
+                parent_coin_info: Bytes32::new([4; 32]),
+                inner_puzzle_hash: Bytes32::new([5; 32]),
+                amount: 1,
+            },
+            prev_subtotal: 0,
+            extra_delta: -1,
+        })
+        .expect("construct solution");
+
+    recognize_puzzle_and_solution(&node_bytes(ctx, puzzle), &node_bytes(ctx, solution))
+}
+
+#[test]
+fn classifies_revealed_genesis_by_coin_id_tail() {
+    let mut ctx = SpendContext::new();
+    let tail_mod = node_from_bytes(&mut ctx, &GENESIS_BY_COIN_ID).expect("tail mod");
+    let tail = ctx
+        .alloc(&CurriedProgram {
+            program: tail_mod,
+            args: clvm_curried_args!(Bytes32::new([8; 32])),
+        })
+        .expect("tail");
+
+    let recognition = recognize_tail_reveal(&mut ctx, tail);
+    let params = &recognition.wrappers[0].params;
+    assert_eq!(params["tail_kind"], "genesis_by_coin_id");
+    assert_eq!(params["tail_matches_asset_id"], true);
+    assert_eq!(params["supply_change"]["kind"], "melt");
+    assert_eq!(params["supply_change"]["summary"], "melting 1 token mojos");
+}
+
+#[test]
+fn classifies_revealed_everything_with_signature_tail() {
+    let mut ctx = SpendContext::new();
+    let tail_mod = node_from_bytes(&mut ctx, &EVERYTHING_WITH_SIGNATURE).expect("tail mod");
+    let tail = ctx
+        .alloc(&CurriedProgram {
+            program: tail_mod,
+            args: clvm_curried_args!(PublicKey::default()),
+        })
+        .expect("tail");
+
+    let recognition = recognize_tail_reveal(&mut ctx, tail);
+    let params = &recognition.wrappers[0].params;
+    assert_eq!(params["tail_kind"], "everything_with_signature");
+    assert_eq!(params["tail_matches_asset_id"], true);
+}
+
 #[test]
 fn recognizes_singleton_did_standard_layers() {
     let mut ctx = SpendContext::new();
@@ -170,6 +409,50 @@ fn recognizes_singleton_nft_state_ownership_standard_layers() {
             "standard_layer"
         ]
     );
+    assert_eq!(recognition.inner_most_layer.as_deref(), Some("standard_layer"));
+}
+
+#[test]
+fn recognizes_p2_delegated_puzzle_without_standard_ambiguity() {
+    let mut ctx = SpendContext::new();
+    let mod_ptr = node_from_bytes(&mut ctx, &P2_DELEGATED_PUZZLE).expect("mod");
+    let puzzle = ctx
+        .alloc(&CurriedProgram {
+            program: mod_ptr,
+            args: clvm_curried_args!(PublicKey::default()),
+        })
+        .expect("construct puzzle");
+    let solution = ctx
+        .alloc(&(NodePtr::NIL, (NodePtr::NIL, ())))
+        .expect("construct solution");
+
+    let recognition = recognize_puzzle_and_solution(&node_bytes(&ctx, puzzle), &node_bytes(&ctx, solution));
+    assert!(recognition.recognized);
+    assert_eq!(wrapper_names(&recognition), vec!["p2_delegated_puzzle_layer"]);
+    assert_eq!(recognition.candidates.len(), 1);
+    let evidence = recognition.wrappers[0].evidence.as_ref().expect("evidence");
+    assert_eq!(evidence.matched_by, "curried_mod_hash");
+    assert_eq!(evidence.curried_arg_count, Some(1));
+    assert_eq!(evidence.solution_status, "ok");
+}
+
+#[test]
+fn recognizes_singleton_launcher_solution() {
+    let mut ctx = SpendContext::new();
+    let puzzle = node_from_bytes(&mut ctx, &SINGLETON_LAUNCHER).expect("launcher");
+    let singleton_puzzle_hash = Bytes32::new([7; 32]);
+    let solution = ctx
+        .alloc(&(singleton_puzzle_hash, (1_u64, (NodePtr::NIL, ()))))
+        .expect("construct solution");
+
+    let recognition = recognize_puzzle_and_solution(&node_bytes(&ctx, puzzle), &node_bytes(&ctx, solution));
+    assert_eq!(wrapper_names(&recognition), vec!["singleton_launcher"]);
+    let launcher = &recognition.parsed_solution.expect("parsed solution")["layers"][0]["result"];
+    assert_eq!(launcher["amount"], 1);
+    assert_eq!(
+        launcher["singleton_puzzle_hash"],
+        format!("0x{}", hex::encode(singleton_puzzle_hash))
+    );
 }
 
 #[test]
@@ -180,4 +463,56 @@ fn unknown_raw_puzzle_is_not_recognized() {
     let recognition = recognize_puzzle_and_solution(&node_bytes(&ctx, puzzle), &node_bytes(&ctx, solution));
     assert!(!recognition.recognized);
     assert!(recognition.wrappers.is_empty());
+    assert!(recognition.inner_most_layer.is_none());
+    assert!(recognition.unrecognized_curried.is_none());
+}
+
+#[test]
+fn unknown_curried_puzzle_reports_mod_and_args() {
+    let mut ctx = SpendContext::new();
+    let tail_mod = node_from_bytes(&mut ctx, &GENESIS_BY_COIN_ID).expect("tail mod");
+    let arg = ctx.alloc(&Bytes32::new([8; 32])).expect("arg");
+    let puzzle = ctx
+        .alloc(&CurriedProgram {
+            program: tail_mod,
+            args: clvm_curried_args!(arg),
+        })
+        .expect("puzzle");
+
+    let recognition = recognize_puzzle_and_solution(&node_bytes(&ctx, puzzle), &[0x80]);
+    assert!(!recognition.recognized);
+    let curried = recognition.unrecognized_curried.expect("curried fallback");
+    let hex = |ptr| format!("0x{}", hex::encode(tree_hash(&ctx, ptr).to_bytes()));
+    assert_eq!(curried.puzzle_hash, hex(puzzle));
+    assert_eq!(curried.mod_hash, hex(tail_mod));
+    assert_eq!(curried.arg_count, 1);
+    assert_eq!(curried.arg_tree_hashes, vec![hex(arg)]);
+}
+
+#[test]
+fn settlement_solution_is_structured() {
+    let mut ctx = SpendContext::new();
+    let puzzle = SettlementLayer.construct_puzzle(&mut ctx).expect("construct puzzle");
+    let solution = SettlementLayer
+        .construct_solution(
+            &mut ctx,
+            SettlementPaymentsSolution {
+                notarized_payments: vec![NotarizedPayment {
+                    nonce: Bytes32::new([3; 32]),
+                    payments: vec![Payment::new(Bytes32::new([4; 32]), 1000, Memos::None)],
+                }],
+            },
+        )
+        .expect("construct solution");
+
+    let recognition =
+        recognize_puzzle_and_solution(&node_bytes(&ctx, puzzle), &node_bytes(&ctx, solution));
+    assert_eq!(wrapper_names(&recognition), vec!["settlement_layer"]);
+    let result = &recognition.parsed_solution.expect("parsed solution")["layers"][0]["result"];
+    assert!(result.get("parsed_debug").is_none());
+    let notarized = &result["notarized_payments"][0];
+    assert_eq!(notarized["nonce"], format!("0x{}", "03".repeat(32)));
+    assert_eq!(notarized["payments"][0]["puzzle_hash"], format!("0x{}", "04".repeat(32)));
+    assert_eq!(notarized["payments"][0]["amount"], 1000);
+    assert_eq!(notarized["payments"][0]["memos"], serde_json::json!([]));
 }
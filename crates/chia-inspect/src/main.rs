@@ -1,11 +1,32 @@
+#[cfg(feature = "tui")]
+mod tui;
+
 use std::io::{Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
 
-use anyhow::Result;
+use anyhow::{Context, Result, anyhow, bail};
+use chia_inspect_core::cache::RecognitionCache;
+use chia_inspect_core::conditions::diff_conditions;
+use chia_inspect_core::display::abbreviate_display_hex;
+use chia_inspect_core::error::InspectError;
+use chia_inspect_core::events::output_events;
+use chia_inspect_core::graph::render_dot;
+use chia_inspect_core::markdown::render_markdown;
+use chia_inspect_core::network::{Network, constants_from_json};
+use chia_inspect_core::readiness::verdict;
+use chia_inspect_core::recognize::RecognizeOptions;
+use chia_inspect_core::registry::CatRegistry;
+use chia_inspect_core::schema::{ConditionInfo, InspectionOutput, validate_output_document};
 use chia_inspect_core::{
-    ExplainLevel, inspect_bundle, load_block_spends_input, load_coin_spend_input,
-    load_mempool_blob_input,
+    CoinOrder, ExplainLevel, InspectConfig, LoadedInput, ProgressCallback,
+    inspect_bundle_streaming, inspect_bundle_with_config, load_autodetected_input,
+    load_block_spends_input, load_coin_parts_input, load_coin_spend_input, load_generator_input,
+    load_mempool_blob_input, load_mempool_dump_input, load_spent_context,
+    normalized_spend_bundle_json, resolve_generator_refs,
 };
 use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{Value, json};
 
 #[derive(Debug, Parser)]
 #[command(
@@ -13,8 +34,10 @@ use clap::{Parser, Subcommand, ValueEnum};
     about = "Inspect Chia spend bundles and CLVM puzzle behavior"
 )]
 struct Cli {
+    /// With no subcommand, reads stdin and autodetects its format (offer
+    /// string, spend bundle hex, or any accepted JSON shape)
     #[command(subcommand)]
-    command: Command,
+    command: Option<Command>,
 
     #[arg(long, value_enum, default_value_t = ExplainLevelArg::Deep)]
     explain_level: ExplainLevelArg,
@@ -22,12 +45,194 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     pretty: bool,
 
+    /// Render the inspection as JSON or as a paste-ready markdown report
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Json,
+        conflicts_with_all = ["split_output", "pretty_hex", "events", "stream"]
+    )]
+    format: OutputFormat,
+
+    /// Order of summary removals/additions
+    #[arg(long, value_enum, default_value_t = CoinOrderArg::CoinId)]
+    sort_coins: CoinOrderArg,
+
+    /// Also emit puzzle reveal/solution hex re-serialized without backrefs
+    #[arg(long, default_value_t = false)]
+    canonicalize_backrefs: bool,
+
+    /// Chain height used to judge absolute height timelocks in the readiness report
+    #[arg(long)]
+    current_height: Option<u32>,
+
+    /// Unix timestamp used to judge absolute seconds timelocks in the readiness report
+    #[arg(long)]
+    current_timestamp: Option<u64>,
+
+    /// Mempool fee rate in mojos per cost; the readiness report gains the minimum fee
+    #[arg(long, value_name = "MOJOS")]
+    fee_per_cost: Option<u64>,
+
+    /// Check the signature under each network's genesis challenge and report
+    /// which accept the bundle; repeatable or comma-separated
+    #[arg(long, value_enum, value_delimiter = ',')]
+    try_networks: Vec<NetworkArg>,
+
+    /// JSON of consensus constants (genesis_challenge, AGG_SIG additional data, cost limits)
+    /// for a simnet or private network; validation uses them instead of mainnet's
+    #[arg(long, value_name = "PATH")]
+    constants: Option<String>,
+
+    /// Attach the consensus layer's full spend bundle conditions under
+    /// result.debug.consensus, for checking the schema mapping
+    #[arg(long, default_value_t = false)]
+    dump_consensus: bool,
+
+    /// Warn about any spend whose cost reaches this value (HIGH_COST_SPEND)
+    #[arg(long, value_name = "COST")]
+    high_cost_threshold: Option<u64>,
+
+    /// Add result.coverage: counts of fully, partially and not recognized spends
+    #[arg(long, default_value_t = false)]
+    coverage: bool,
+
+    /// Exit nonzero at the first bundle that fails validation instead of
+    /// reporting it; mempool-dump names the failing record
+    #[arg(long, default_value_t = false)]
+    fail_fast: bool,
+
+    /// Fail suspect bundles, such as empty ones, instead of only warning
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
+    /// Include per-operator occurrence counts in static_features
+    #[arg(long, default_value_t = false)]
+    operator_histogram: bool,
+
+    /// Operator set used for disassembly and operator names (default: latest)
+    #[arg(long, value_name = "N")]
+    operator_version: Option<usize>,
+
+    /// Fail any spend (and the bundle) that emits this condition, e.g. AGG_SIG_UNSAFE;
+    /// repeatable or comma-separated
+    #[arg(long = "forbid-condition", value_name = "NAME", value_delimiter = ',')]
+    forbidden_conditions: Vec<String>,
+
+    /// Skip puzzle layer recognition; recognition is reported empty/unrecognized
+    #[arg(long, default_value_t = false)]
+    no_recognition: bool,
+
+    /// Recognize puzzle structure only, without parsing solutions
+    #[arg(long, default_value_t = false)]
+    skip_solutions: bool,
+
+    /// Drop the parsed_debug dumps from parsed solutions, keeping structured fields
+    #[arg(long, default_value_t = false)]
+    compact_recognition: bool,
+
+    /// Stamp SDK layers with this chia-wallet-sdk repo instead of the one built in
+    #[arg(long, value_name = "OWNER/REPO")]
+    source_repo: Option<String>,
+
+    /// Stamp SDK layers with this chia-wallet-sdk ref instead of the one built in
+    #[arg(long, value_name = "REF")]
+    source_ref: Option<String>,
+
+    /// Persist solution-independent recognition here; recognition becomes structure-only
+    #[arg(long)]
+    cache_dir: Option<String>,
+
+    /// JSON mapping CAT asset ids to {name, code, tail_type}; annotates cat_layer params
+    #[arg(long)]
+    cat_registry: Option<String>,
+
+    /// JSON mapping spent coin ids to {confirmed_height, confirmed_timestamp} for timelock checks
+    #[arg(long)]
+    spent_context: Option<String>,
+
+    /// Print a progress line to stderr every N spends analyzed (default 100)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "100")]
+    progress: Option<usize>,
+
     #[arg(long, default_value = "-")]
     output: String,
+
+    /// Write one JSON file per spend plus summary.json into this directory instead of --output
+    #[arg(long, conflicts_with = "output")]
+    split_output: Option<String>,
+
+    /// Abbreviate hex longer than N bytes (default 32) in disassembly/display fields;
+    /// byte fields keep full fidelity
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "32")]
+    pretty_hex: Option<usize>,
+
+    /// Truncate each puzzle/solution disassembly to N characters; the hex stays complete
+    #[arg(long, value_name = "N")]
+    max_disasm_len: Option<usize>,
+
+    /// Emit one flat JSON event per line ({bundle_id, spend_index, type, ...}) instead of a document
+    #[arg(long, default_value_t = false, conflicts_with = "split_output")]
+    events: bool,
+
+    /// Emit output only for bundles with a warning or a failed status: mempool-dump
+    /// drops clean entries, and a clean single bundle writes nothing
+    #[arg(long, default_value_t = false)]
+    only_warnings: bool,
+
+    /// Emit only {bundle_id, status, submittable, fee_mojos, blockers} instead of the document
+    #[arg(long, default_value_t = false, conflicts_with_all = ["split_output", "events", "format"])]
+    verdict: bool,
+
+    /// Write the document incrementally, each spend as soon as it is analyzed (compact JSON only)
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["pretty", "split_output", "pretty_hex", "events", "verdict", "only_warnings"]
+    )]
+    stream: bool,
 }
 
 #[derive(Debug, Subcommand)]
 enum Command {
+    #[command(flatten)]
+    Input(InputCommand),
+    /// Inspect every item of a get_all_mempool_items response
+    MempoolDump {
+        /// Path to the `{bundle_name: mempool_item}` JSON (or - for stdin)
+        #[arg(long)]
+        path: String,
+    },
+    /// Emit any accepted input as canonical spend bundle JSON instead of inspecting it
+    Normalize {
+        #[command(subcommand)]
+        input: InputCommand,
+    },
+    /// Render an inspection output document as a coin graph
+    Graph {
+        /// Path to a chia-inspect output document (or - for stdin)
+        #[arg(long)]
+        inspection_json: String,
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+    },
+    /// Check that an inspection output document matches a supported schema version
+    ValidateOutput {
+        #[arg(long)]
+        path: String,
+    },
+    /// Browse an inspection output document interactively
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Path to a chia-inspect output document (or - for stdin)
+        #[arg(long)]
+        inspection_json: String,
+    },
+}
+
+/// The input shapes that load into a single spend bundle.
+#[derive(Debug, Subcommand)]
+enum InputCommand {
     /// Inspect a mempool blob containing spend bundle data
     Mempool {
         #[arg(long)]
@@ -40,11 +245,85 @@ enum Command {
     },
     /// Inspect a single coin spend payload
     Coin {
+        #[arg(long, required_unless_present = "coin_json")]
+        coin_spend_json: Option<String>,
+        /// Coin metadata JSON; pair with --puzzle-file and --solution-file
+        #[arg(long, conflicts_with = "coin_spend_json", requires_all = ["puzzle_file", "solution_file"])]
+        coin_json: Option<String>,
+        /// Puzzle reveal hex file (or - for stdin)
+        #[arg(long, requires = "coin_json")]
+        puzzle_file: Option<String>,
+        /// Solution hex file (or - for stdin)
+        #[arg(long, requires = "coin_json")]
+        solution_file: Option<String>,
+        /// Expected conditions JSON (`[{opcode, args}]`); prints the difference
+        /// instead of the inspection and exits nonzero on any mismatch
+        #[arg(long)]
+        expect_conditions: Option<String>,
+    },
+    /// Run a block generator program and inspect the coin spends it produces
+    Generator {
+        /// Path to the generator program hex (or - for stdin)
         #[arg(long)]
-        coin_spend_json: String,
+        path: String,
+        /// Paths to referenced generator program hex files, in reference order
+        #[arg(long)]
+        refs: Vec<String>,
+        /// JSON mapping block heights to referenced generator hex, instead of --refs
+        #[arg(long, value_name = "PATH", conflicts_with = "refs")]
+        generator_refs: Option<String>,
+        /// The block's generator ref list, comma-separated; defaults to every height in
+        /// --generator-refs, ascending
+        #[arg(long, value_delimiter = ',', requires = "generator_refs")]
+        ref_heights: Vec<u32>,
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Json,
+    /// GitHub-flavored markdown: summary, value flow, warnings, per-spend layers and conditions
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    /// Render through Graphviz `dot -Tsvg` (must be on PATH)
+    Svg,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CoinOrderArg {
+    CoinId,
+    /// Largest amount first, then coin id
+    Amount,
+}
+
+impl From<CoinOrderArg> for CoinOrder {
+    fn from(value: CoinOrderArg) -> Self {
+        match value {
+            CoinOrderArg::CoinId => CoinOrder::CoinId,
+            CoinOrderArg::Amount => CoinOrder::AmountDesc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum NetworkArg {
+    Mainnet,
+    Testnet11,
+}
+
+impl From<NetworkArg> for Network {
+    fn from(value: NetworkArg) -> Self {
+        match value {
+            NetworkArg::Mainnet => Network::Mainnet,
+            NetworkArg::Testnet11 => Network::Testnet11,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum ExplainLevelArg {
     Conditions,
@@ -60,33 +339,414 @@ impl From<ExplainLevelArg> for ExplainLevel {
     }
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let explain_level = ExplainLevel::from(cli.explain_level);
+/// Why a run failed, for the exit-code contract: 0 success, 1 validation
+/// failure, 2 unreadable input, 3 anything else (an internal error).
+#[derive(Debug, Clone, Copy)]
+enum Failure {
+    Validation,
+    Input,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Validation => "the bundle failed validation",
+            Self::Input => "the input could not be read",
+        })
+    }
+}
+
+fn exit_code(err: &anyhow::Error) -> u8 {
+    match err.downcast_ref::<Failure>() {
+        Some(Failure::Validation) => return 1,
+        Some(Failure::Input) => return 2,
+        None => {}
+    }
+    match err.downcast_ref::<InspectError>() {
+        Some(InspectError::ValidationFailed(_)) => 1,
+        Some(_) => 2,
+        None => 3,
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let config = InspectConfig {
+        explain_level: ExplainLevel::from(cli.explain_level),
+        coin_order: CoinOrder::from(cli.sort_coins),
+        canonicalize_backrefs: cli.canonicalize_backrefs,
+        current_height: cli.current_height,
+        current_timestamp: cli.current_timestamp,
+        operator_histogram: cli.operator_histogram,
+        operator_version: cli.operator_version,
+        forbidden_conditions: cli.forbidden_conditions.clone(),
+        cat_registry: cli
+            .cat_registry
+            .as_deref()
+            .map(|path| CatRegistry::from_json(&read_input(path)?))
+            .transpose()
+            .context(Failure::Input)?,
+        fee_per_cost: cli.fee_per_cost,
+        try_networks: cli.try_networks.iter().copied().map(Network::from).collect(),
+        dump_consensus: cli.dump_consensus,
+        high_cost_threshold: cli.high_cost_threshold,
+        coverage: cli.coverage,
+        fail_fast: cli.fail_fast,
+        strict: cli.strict,
+        max_disasm_len: cli.max_disasm_len,
+        constants: match &cli.constants {
+            Some(path) => {
+                let (constants, ignored) =
+                    constants_from_json(&read_input(path)?).context(Failure::Input)?;
+                if !ignored.is_empty() {
+                    eprintln!("chia-inspect: ignoring constants {}", ignored.join(", "));
+                }
+                Some(constants)
+            }
+            None => None,
+        },
+        recognize_options: RecognizeOptions {
+            skip_solutions: cli.skip_solutions,
+            compact: cli.compact_recognition,
+        },
+        skip_recognition: cli.no_recognition,
+        source_repo: cli.source_repo.clone(),
+        source_ref: cli.source_ref.clone(),
+        recognition_cache: cli.cache_dir.as_deref().map(RecognitionCache::open).transpose()?,
+        spent_context: match &cli.spent_context {
+            Some(path) => load_spent_context(&read_input(path)?).context(Failure::Input)?,
+            None => Default::default(),
+        },
+        progress: cli.progress.map(|every| {
+            let every = every.max(1);
+            ProgressCallback::new(move |done, total| {
+                if done % every == 0 || done == total {
+                    eprintln!("chia-inspect: analyzed {done}/{total} spends");
+                }
+            })
+        }),
+    };
 
     let (source, bundle, notes) = match &cli.command {
-        Command::Mempool { blob_json } => load_mempool_blob_input(&read_input(blob_json)?)?,
-        Command::Block { spends_json } => load_block_spends_input(&read_input(spends_json)?)?,
-        Command::Coin { coin_spend_json } => load_coin_spend_input(&read_input(coin_spend_json)?)?,
+        None => load_autodetected_input(&read_input("-")?).context(Failure::Input)?,
+        Some(Command::Input(input)) => load_input(input)?,
+        Some(Command::MempoolDump { path }) => return inspect_mempool_dump(path, &config, &cli),
+        Some(Command::Normalize { input }) => {
+            let (_source, bundle, _notes) = load_input(input)?;
+            let normalized = normalized_spend_bundle_json(&bundle);
+            return write_output(&cli.output, &to_json(&normalized, cli.pretty)?);
+        }
+        Some(Command::Graph {
+            inspection_json,
+            format,
+        }) => return write_graph(inspection_json, *format, &cli),
+        Some(Command::ValidateOutput { path }) => return validate_output(path, &cli),
+        #[cfg(feature = "tui")]
+        Some(Command::Tui { inspection_json }) => {
+            return tui::run(validate_output_document(&read_input(inspection_json)?)?);
+        }
     };
 
-    let output = inspect_bundle(source, bundle, notes, explain_level)?;
-    let serialized = if cli.pretty {
-        serde_json::to_string_pretty(&output)?
-    } else {
-        serde_json::to_string(&output)?
+    if let Some(Command::Input(InputCommand::Coin {
+        expect_conditions: Some(path),
+        ..
+    })) = &cli.command
+    {
+        let output = inspect_bundle_with_config(source, bundle, notes, &config)?;
+        return check_expected_conditions(&output, path, &cli);
+    }
+    if cli.verdict {
+        let output = inspect_bundle_with_config(source, bundle, notes, &config)?;
+        if cli.only_warnings && !needs_attention(&output) {
+            return Ok(());
+        }
+        write_output(&cli.output, &to_json(&serde_json::to_value(verdict(&output))?, cli.pretty)?)?;
+        return strict_verdict(&output, &cli);
+    }
+    if cli.stream {
+        let output = stream_output(&cli.output, (source, bundle, notes), &config)?;
+        return strict_verdict(&output, &cli);
+    }
+    let output = inspect_bundle_with_config(source, bundle, notes, &config)?;
+    if cli.only_warnings && !needs_attention(&output) {
+        return Ok(());
+    }
+    if cli.format == OutputFormat::Markdown {
+        write_output(&cli.output, render_markdown(&output).trim_end())?;
+        return strict_verdict(&output, &cli);
+    }
+    if cli.events {
+        let lines = output_events(&output)
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<serde_json::Result<Vec<_>>>()?;
+        write_output(&cli.output, &lines.join("\n"))?;
+        return strict_verdict(&output, &cli);
+    }
+    match &cli.split_output {
+        Some(dir) => write_split_output(Path::new(dir), &output, cli.pretty)?,
+        None => {
+            let mut document = serde_json::to_value(&output)?;
+            if let Some(max_bytes) = cli.pretty_hex {
+                abbreviate_display_hex(&mut document, max_bytes);
+            }
+            write_output(&cli.output, &to_json(&document, cli.pretty)?)?
+        }
+    }
+    strict_verdict(&output, &cli)
+}
+
+/// Under `--strict`, a failed bundle fails the run once its document has
+/// been written.
+fn strict_verdict(output: &InspectionOutput, cli: &Cli) -> Result<()> {
+    let Some(error) = output.result.error.as_ref().filter(|_| cli.strict) else {
+        return Ok(());
     };
-    write_output(&cli.output, &serialized)?;
+    Err(anyhow!("{}: {}", error.kind, error.message).context(Failure::Validation))
+}
+
+fn stream_output(
+    path_or_stdout: &str,
+    input: LoadedInput,
+    config: &InspectConfig,
+) -> Result<InspectionOutput> {
+    let (source, bundle, notes) = input;
+    if path_or_stdout == "-" {
+        let mut stdout = std::io::stdout().lock();
+        return inspect_bundle_streaming(source, bundle, notes, config, &mut stdout);
+    }
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path_or_stdout)?);
+    inspect_bundle_streaming(source, bundle, notes, config, &mut file)
+}
+
+fn load_input(input: &InputCommand) -> Result<LoadedInput> {
+    load_input_shape(input).context(Failure::Input)
+}
+
+fn load_input_shape(input: &InputCommand) -> Result<LoadedInput> {
+    match input {
+        InputCommand::Mempool { blob_json } => load_mempool_blob_input(&read_input(blob_json)?),
+        InputCommand::Block { spends_json } => load_block_spends_input(&read_input(spends_json)?),
+        InputCommand::Coin {
+            coin_spend_json,
+            coin_json,
+            puzzle_file,
+            solution_file,
+            ..
+        } => match (coin_spend_json, coin_json, puzzle_file, solution_file) {
+            (Some(path), ..) => load_coin_spend_input(&read_input(path)?),
+            (None, Some(coin), Some(puzzle), Some(solution)) => load_coin_parts_input(
+                &read_input(coin)?,
+                &read_input(puzzle)?,
+                &read_input(solution)?,
+            ),
+            _ => bail!("coin needs --coin-spend-json or --coin-json with --puzzle-file and --solution-file"),
+        },
+        InputCommand::Generator {
+            path,
+            refs,
+            generator_refs,
+            ref_heights,
+        } => {
+            let refs = match generator_refs {
+                Some(refs_path) => resolve_generator_refs(&read_input(refs_path)?, ref_heights)?,
+                None => refs
+                    .iter()
+                    .map(|path| read_input(path))
+                    .collect::<Result<Vec<_>>>()?,
+            };
+            load_generator_input(&read_input(path)?, &refs)
+        }
+    }
+}
+
+/// Inspects each mempool item on its own; a failure is recorded against its
+/// bundle name instead of aborting the batch.
+fn inspect_mempool_dump(path: &str, config: &InspectConfig, cli: &Cli) -> Result<()> {
+    let mut entries = Vec::new();
+    let mut failed = 0;
+    let items = load_mempool_dump_input(&read_input(path)?).context(Failure::Input)?;
+    for (idx, (bundle_name, item)) in items.into_iter().enumerate() {
+        let unparsed = item.is_err();
+        let inspected = item.and_then(|(source, bundle, notes)| {
+            inspect_bundle_with_config(source, bundle, notes, config)
+        });
+        entries.push(match inspected {
+            Ok(output) if cli.only_warnings && !needs_attention(&output) => continue,
+            Ok(output) => {
+                if output.result.status == "failed" {
+                    failed += 1;
+                }
+                json!({ "bundle_name": bundle_name, "output": output })
+            }
+            Err(err) if config.fail_fast => {
+                write_output(&cli.output, &to_json(&Value::Array(entries), cli.pretty)?)?;
+                let err = err.context(format!("record {idx} ({bundle_name}) failed"));
+                return Err(if unparsed { err.context(Failure::Input) } else { err });
+            }
+            Err(err) => json!({ "bundle_name": bundle_name, "error": format!("{err:#}") }),
+        });
+    }
+    write_output(&cli.output, &to_json(&Value::Array(entries), cli.pretty)?)?;
+    if cli.strict && failed > 0 {
+        return Err(anyhow!("{failed} bundle(s) have result.status failed").context(Failure::Validation));
+    }
     Ok(())
 }
 
+/// What `--only-warnings` keeps: bundles that failed or raised a warning.
+fn needs_attention(output: &InspectionOutput) -> bool {
+    output.result.status == "failed" || !output.result.warnings.is_empty()
+}
+
+/// Compares the coin spend's conditions with the expected list and writes
+/// the difference; a mismatch fails the run so CI can assert on it.
+fn check_expected_conditions(output: &InspectionOutput, path: &str, cli: &Cli) -> Result<()> {
+    let expected: Vec<ConditionInfo> = serde_json::from_str(&read_input(path)?)
+        .context("expected conditions must be a JSON list of {opcode, args}")
+        .context(Failure::Input)?;
+    let spend = output.result.spends.first().context("coin input produced no spend")?;
+    let diff = diff_conditions(&expected, &spend.evaluation.conditions);
+    let report = json!({
+        "matches": diff.is_empty(),
+        "evaluation_status": spend.evaluation.status,
+        "missing": diff.missing,
+        "unexpected": diff.unexpected,
+    });
+    write_output(&cli.output, &to_json(&report, cli.pretty)?)?;
+    if !diff.is_empty() {
+        let mismatch = anyhow!(
+            "conditions differ from {path}: {} missing, {} unexpected",
+            diff.missing.len(),
+            diff.unexpected.len()
+        );
+        return Err(mismatch.context(Failure::Validation));
+    }
+    Ok(())
+}
+
+fn validate_output(path: &str, cli: &Cli) -> Result<()> {
+    let output = validate_output_document(&read_input(path)?)?;
+    let report = json!({
+        "valid": true,
+        "schema_version": output.schema_version,
+        "spends": output.result.spends.len(),
+    });
+    write_output(&cli.output, &to_json(&report, cli.pretty)?)
+}
+
+fn write_graph(inspection_json: &str, format: GraphFormat, cli: &Cli) -> Result<()> {
+    let output = validate_output_document(&read_input(inspection_json)?)?;
+    let dot = render_dot(&output);
+    match format {
+        GraphFormat::Dot => write_output(&cli.output, dot.trim_end()),
+        GraphFormat::Svg => {
+            let mut child = std::process::Command::new("dot")
+                .arg("-Tsvg")
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .context("failed to run graphviz `dot`; install graphviz or use --format dot")?;
+            child
+                .stdin
+                .take()
+                .context("graphviz stdin unavailable")?
+                .write_all(dot.as_bytes())?;
+            let rendered = child.wait_with_output()?;
+            if !rendered.status.success() {
+                bail!("graphviz `dot` exited with {}", rendered.status);
+            }
+            write_output(&cli.output, String::from_utf8_lossy(&rendered.stdout).trim_end())
+        }
+    }
+}
+
+fn to_json(value: &Value, pretty: bool) -> Result<String> {
+    Ok(if pretty {
+        serde_json::to_string_pretty(value)?
+    } else {
+        serde_json::to_string(value)?
+    })
+}
+
+/// Reads a file (or stdin for `-`) as text, transparently decompressing
+/// gzip and zstd input recognized by its magic bytes.
 fn read_input(path_or_stdin: &str) -> Result<String> {
+    read_text(path_or_stdin).context(Failure::Input)
+}
+
+fn read_text(path_or_stdin: &str) -> Result<String> {
+    let mut raw = Vec::new();
     if path_or_stdin == "-" {
-        let mut input = String::new();
-        std::io::stdin().read_to_string(&mut input)?;
-        return Ok(input);
+        std::io::stdin().read_to_end(&mut raw)?;
+    } else {
+        raw = std::fs::read(path_or_stdin)?;
+    }
+    let decompressed = match Compression::detect(&raw) {
+        Some(compression) => compression.decompress(&raw).with_context(|| {
+            format!("failed to decompress {path_or_stdin} as {}", compression.name())
+        })?,
+        None => {
+            if let Some(compression) = Compression::from_extension(path_or_stdin) {
+                bail!("{path_or_stdin} is named like {} but is not", compression.name());
+            }
+            raw
+        }
+    };
+    String::from_utf8(decompressed).with_context(|| format!("{path_or_stdin} is not UTF-8 text"))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    fn from_extension(path: &str) -> Option<Self> {
+        match Path::new(path).extension()?.to_str()? {
+            "gz" => Some(Self::Gzip),
+            "zst" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    fn decompress(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        match self {
+            Self::Gzip => {
+                flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut out)?;
+            }
+            Self::Zstd => out = zstd::stream::decode_all(bytes)?,
+        }
+        Ok(out)
     }
-    Ok(std::fs::read_to_string(path_or_stdin)?)
 }
 
 fn write_output(path_or_stdout: &str, data: &str) -> Result<()> {
@@ -100,3 +760,19 @@ fn write_output(path_or_stdout: &str, data: &str) -> Result<()> {
     std::fs::write(path_or_stdout, format!("{data}\n"))?;
     Ok(())
 }
+
+fn write_split_output(dir: &Path, output: &InspectionOutput, pretty: bool) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (index, spend) in output.result.spends.iter().enumerate() {
+        let coin_id = spend.coin_spend.coin.coin_id.trim_start_matches("0x");
+        let path = dir.join(format!("spend_{index}_{coin_id}.json"));
+        let data = to_json(&serde_json::to_value(spend)?, pretty)?;
+        std::fs::write(path, format!("{data}\n"))?;
+    }
+
+    let mut summary = output.clone();
+    summary.result.spends.clear();
+    let data = to_json(&serde_json::to_value(&summary)?, pretty)?;
+    std::fs::write(dir.join("summary.json"), format!("{data}\n"))?;
+    Ok(())
+}
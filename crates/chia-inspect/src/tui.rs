@@ -0,0 +1,252 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use chia_inspect_core::schema::{InspectionOutput, SpendAnalysis};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::Value;
+
+/// Interactive browser over an inspection document: a spend list on the
+/// left, the selected spend's recognition, conditions and value flow on the
+/// right. `Tab` jumps to the next spend (in bundle order, wrapping) linked to
+/// the selection by an announcement or an ephemeral (created-and-spent) coin.
+pub fn run(output: InspectionOutput) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new(output).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+struct App {
+    output: InspectionOutput,
+    list: ListState,
+    scroll: u16,
+}
+
+impl App {
+    fn new(output: InspectionOutput) -> Self {
+        let selected = (!output.result.spends.is_empty()).then_some(0);
+        Self {
+            output,
+            list: ListState::default().with_selected(selected),
+            scroll: 0,
+        }
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.select(|idx, len| (idx + 1).min(len - 1));
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.select(|idx, _| idx.saturating_sub(1)),
+                KeyCode::Tab => self.follow_link(),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                _ => {}
+            }
+        }
+    }
+
+    fn select(&mut self, step: impl Fn(usize, usize) -> usize) {
+        let len = self.output.result.spends.len();
+        if let Some(idx) = self.list.selected().filter(|_| len > 0) {
+            self.jump(step(idx, len));
+        }
+    }
+
+    fn jump(&mut self, idx: usize) {
+        if self.list.selected() != Some(idx) {
+            self.list.select(Some(idx));
+            self.scroll = 0;
+        }
+    }
+
+    fn follow_link(&mut self) {
+        let Some(idx) = self.list.selected() else {
+            return;
+        };
+        let links = linked_spends(&self.output.result.spends, idx);
+        let target = links
+            .iter()
+            .map(|(target, _)| *target)
+            .find(|target| *target > idx)
+            .or_else(|| links.first().map(|(target, _)| *target));
+        if let Some(target) = target {
+            self.jump(target);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(main);
+
+        let spends = &self.output.result.spends;
+        let items = spends
+            .iter()
+            .enumerate()
+            .map(|(idx, spend)| spend_label(idx, spend))
+            .collect::<Vec<_>>();
+        let title = format!("Spends ({}) {}", spends.len(), self.output.result.status);
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, left, &mut self.list);
+
+        let details = match self.list.selected() {
+            Some(idx) => spend_details(spends, idx),
+            None => vec![Line::from("no spends")],
+        };
+        let paragraph = Paragraph::new(details)
+            .block(Block::bordered().title("Spend"))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        frame.render_widget(paragraph, right);
+
+        frame.render_widget(
+            Line::from(" ↑/↓ select  Tab follow link  PgUp/PgDn scroll  q quit").dim(),
+            footer,
+        );
+    }
+}
+
+fn spend_label(idx: usize, spend: &SpendAnalysis) -> Line<'static> {
+    let coin = &spend.coin_spend.coin;
+    let layer = spend
+        .puzzle
+        .recognition
+        .wrappers
+        .first()
+        .map_or("unrecognized", |wrapper| wrapper.name.as_str());
+    Line::from(format!("#{idx} {} {} {layer}", short_id(&coin.coin_id), coin.amount))
+}
+
+fn spend_details(spends: &[SpendAnalysis], idx: usize) -> Vec<Line<'static>> {
+    let spend = &spends[idx];
+    let coin = &spend.coin_spend.coin;
+    let mut lines = vec![
+        Line::from(format!("coin_id      {}", coin.coin_id)).bold(),
+        Line::from(format!("parent       {}", coin.parent_coin_id)),
+        Line::from(format!("puzzle_hash  {}", coin.puzzle_hash)),
+        Line::from(format!("amount       {}", coin.amount)),
+        Line::from(format!("evaluation   {}", spend.evaluation.status)),
+    ];
+    if let Some(role) = &spend.puzzle_behavior.explanation.role {
+        lines.push(Line::from(format!("role         {role}")));
+    }
+
+    lines.push(heading("Recognition"));
+    if spend.puzzle.recognition.wrappers.is_empty() {
+        lines.push(Line::from("  unrecognized"));
+    }
+    for wrapper in &spend.puzzle.recognition.wrappers {
+        lines.push(Line::from(format!("  {}", wrapper.name)));
+        if let Value::Object(params) = &wrapper.params {
+            for (key, value) in params {
+                lines.push(Line::from(format!("    {key}: {}", compact(value))).dim());
+            }
+        }
+    }
+
+    lines.push(heading("Conditions"));
+    for condition in &spend.evaluation.conditions {
+        let args = condition.args.iter().map(compact).collect::<Vec<_>>();
+        lines.push(Line::from(format!("  {} {}", condition.opcode, args.join(" "))));
+    }
+
+    lines.push(heading("Value flow"));
+    for flow in &spend.puzzle_behavior.explanation.value_flow {
+        lines.push(Line::from(format!("  {}", compact(flow))));
+    }
+
+    lines.push(heading("Links (Tab)"));
+    let links = linked_spends(spends, idx);
+    if links.is_empty() {
+        lines.push(Line::from("  none"));
+    }
+    for (target, reason) in links {
+        let target_coin = &spends[target].coin_spend.coin.coin_id;
+        lines.push(Line::from(format!("  #{target} {} {reason}", short_id(target_coin))));
+    }
+    lines
+}
+
+/// Spends tied to `idx`, in spend order: the parent that created this coin,
+/// ephemeral children this spend creates, and both sides of every
+/// announcement this spend makes or asserts.
+fn linked_spends(spends: &[SpendAnalysis], idx: usize) -> Vec<(usize, &'static str)> {
+    let spend = &spends[idx];
+    let created = spend
+        .evaluation
+        .additions
+        .iter()
+        .map(|coin| coin.coin_id.as_str())
+        .collect::<BTreeSet<_>>();
+    let announced = announcement_ids(&spend.evaluation.announcements);
+    let asserted = announcement_ids(&spend.evaluation.assertions);
+
+    let mut links = Vec::new();
+    for (other_idx, other) in spends.iter().enumerate() {
+        if other_idx == idx {
+            continue;
+        }
+        let reason = if other
+            .evaluation
+            .additions
+            .iter()
+            .any(|coin| coin.coin_id == spend.coin_spend.coin.coin_id)
+        {
+            "created this coin"
+        } else if created.contains(other.coin_spend.coin.coin_id.as_str()) {
+            "spends a coin created here"
+        } else if !announced.is_disjoint(&announcement_ids(&other.evaluation.assertions)) {
+            "asserts an announcement made here"
+        } else if !asserted.is_disjoint(&announcement_ids(&other.evaluation.announcements)) {
+            "makes an announcement asserted here"
+        } else {
+            continue;
+        };
+        links.push((other_idx, reason));
+    }
+    links
+}
+
+fn announcement_ids(values: &[Value]) -> BTreeSet<&str> {
+    values
+        .iter()
+        .filter_map(|value| value.get("announcement_id").and_then(Value::as_str))
+        .collect()
+}
+
+fn heading(title: &str) -> Line<'static> {
+    Line::from(format!("── {title}")).bold()
+}
+
+fn short_id(id: &str) -> String {
+    let digits = id.trim_start_matches("0x");
+    if digits.len() <= 12 {
+        return id.to_string();
+    }
+    format!("0x{}…{}", &digits[..6], &digits[digits.len() - 4..])
+}
+
+fn compact(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
@@ -1,10 +1,15 @@
+use std::io::Read;
+
 use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand};
+use chia_inspect_core::inspect::DEFAULT_MAX_COST;
 use chialisp::classic::clvm::OPERATORS_LATEST_VERSION;
 use chialisp::classic::clvm_tools::binutils::{assemble, disassemble};
 use chialisp::classic::clvm_tools::cmds;
-use clvmr::allocator::Allocator;
-use clvmr::serde::{node_from_bytes_backrefs, node_to_bytes};
+use clvm_utils::tree_hash;
+use clvmr::allocator::{Allocator, NodePtr, SExp};
+use clvmr::serde::{node_from_bytes_backrefs, node_to_bytes, node_to_bytes_backrefs};
+use clvmr::{ChiaDialect, run_program};
 
 #[derive(Debug, Parser)]
 #[command(
@@ -14,20 +19,51 @@ use clvmr::serde::{node_from_bytes_backrefs, node_to_bytes};
 struct Cli {
     #[command(subcommand)]
     command: Command,
+
+    /// Operator set used to name operators when disassembling (opd, equiv)
+    #[arg(long, global = true, value_name = "N", default_value_t = OPERATORS_LATEST_VERSION)]
+    operator_version: usize,
 }
 
 #[derive(Debug, Subcommand)]
 enum Command {
     /// Decode CLVM bytes to readable CLVM
     Opd {
+        /// Hex bytes, or - to read from stdin
         input: String,
     },
     /// Encode readable CLVM to bytes
     Opc {
+        /// CLVM source, or - to read from stdin
+        input: String,
+        /// Print bare hex without the 0x prefix
+        #[arg(long, default_value_t = false)]
+        no_prefix: bool,
+        /// Print hex digits in uppercase
+        #[arg(long, default_value_t = false)]
+        uppercase: bool,
+    },
+    /// Check whether two programs are structurally identical (and optionally behave the same)
+    Equiv {
+        /// CLVM source or hex, or - to read from stdin
+        #[arg(long)]
+        left: String,
+        /// CLVM source or hex, or - to read from stdin
+        #[arg(long)]
+        right: String,
+        /// Also run both programs against this environment and compare outputs
+        #[arg(long)]
+        env: Option<String>,
+    },
+    /// Re-serialize a puzzle with backref compression and report the bytes saved
+    Minimize {
+        /// Puzzle (CLVM source or hex), or - to read from stdin
+        #[arg(long)]
         input: String,
     },
     /// Run CLVM program with environment
     Run {
+        /// Program (CLVM source or hex), or - to read from stdin
         #[arg(long)]
         program: String,
         #[arg(long, default_value = "()")]
@@ -41,23 +77,41 @@ enum Command {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let version = cli.operator_version;
+    if version > OPERATORS_LATEST_VERSION {
+        bail!(
+            "operator version {version} is newer than the latest supported ({OPERATORS_LATEST_VERSION})"
+        );
+    }
     match cli.command {
         Command::Opd { input } => {
-            let bytes = decode_hex_input(&input)?;
+            let bytes = decode_hex_input(&read_input(&input)?)?;
             let mut allocator = Allocator::new();
             let node = node_from_bytes_backrefs(&mut allocator, &bytes)?;
-            println!(
-                "{}",
-                disassemble(&allocator, node, Some(OPERATORS_LATEST_VERSION))
-            );
+            println!("{}", disassemble(&allocator, node, Some(version)));
         }
-        Command::Opc { input } => {
+        Command::Opc {
+            input,
+            no_prefix,
+            uppercase,
+        } => {
             let mut allocator = Allocator::new();
-            let node = assemble(&mut allocator, &input)
+            let node = assemble(&mut allocator, &read_input(&input)?)
                 .map_err(|e| anyhow::anyhow!("failed to assemble CLVM: {e}"))?;
             let bytes = node_to_bytes(&allocator, node)?;
-            println!("0x{}", hex::encode(bytes));
+            let hex = if uppercase {
+                hex::encode_upper(bytes)
+            } else {
+                hex::encode(bytes)
+            };
+            println!("{}{hex}", if no_prefix { "" } else { "0x" });
+        }
+        Command::Equiv { left, right, env } => {
+            if !equiv(&read_input(&left)?, &read_input(&right)?, env.as_deref(), version)? {
+                std::process::exit(1);
+            }
         }
+        Command::Minimize { input } => minimize(&read_input(&input)?)?,
         Command::Run {
             program,
             env,
@@ -71,7 +125,7 @@ fn main() -> Result<()> {
             if verbose {
                 args.push("--verbose".to_string());
             }
-            args.push(normalize_program_input(&program)?);
+            args.push(normalize_program_input(&read_input(&program)?)?);
             args.push(normalize_program_input(&env)?);
             cmds::brun(&args);
         }
@@ -79,6 +133,139 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Mirrors chia-inspect: `-` reads the whole of stdin, anything else is
+/// taken literally. Surrounding whitespace from pipes is trimmed.
+fn read_input(input: &str) -> Result<String> {
+    if input == "-" {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buffer)
+            .context("failed to read stdin")?;
+        return Ok(buffer.trim().to_string());
+    }
+    Ok(input.to_string())
+}
+
+/// Prints the comparison and returns whether the programs are equivalent:
+/// tree-hash equal, and producing the same output when `env` is given.
+fn equiv(left: &str, right: &str, env: Option<&str>, version: usize) -> Result<bool> {
+    let mut allocator = Allocator::new();
+    let left = load_program(&mut allocator, left)?;
+    let right = load_program(&mut allocator, right)?;
+
+    let left_hash = tree_hash(&allocator, left);
+    let right_hash = tree_hash(&allocator, right);
+    let structural = left_hash == right_hash;
+    println!("tree_hash_equal: {structural}");
+    println!("left_tree_hash: 0x{}", hex::encode(left_hash.to_bytes()));
+    println!("right_tree_hash: 0x{}", hex::encode(right_hash.to_bytes()));
+    if !structural {
+        let (path, left_sub, right_sub) = first_difference(&allocator, left, right, String::new());
+        let path = if path.is_empty() { "(root)".to_string() } else { path };
+        println!("first_difference: {path}");
+        println!("  left:  {}", disassemble(&allocator, left_sub, Some(version)));
+        println!("  right: {}", disassemble(&allocator, right_sub, Some(version)));
+    }
+
+    let Some(env) = env else {
+        return Ok(structural);
+    };
+    let env = load_program(&mut allocator, &read_input(env)?)?;
+    let left_out = run_for_equiv(&mut allocator, left, env);
+    let right_out = run_for_equiv(&mut allocator, right, env);
+    let behavioral = match (&left_out, &right_out) {
+        (Ok(l), Ok(r)) => tree_hash(&allocator, *l) == tree_hash(&allocator, *r),
+        (Err(l), Err(r)) => l == r,
+        _ => false,
+    };
+    println!("output_equal: {behavioral}");
+    for (side, out) in [("left", &left_out), ("right", &right_out)] {
+        match out {
+            Ok(node) => println!(
+                "  {side}_output: {}",
+                disassemble(&allocator, *node, Some(version))
+            ),
+            Err(err) => println!("  {side}_error: {err}"),
+        }
+    }
+    Ok(structural && behavioral)
+}
+
+/// Prints the input's size as given and fully expanded, the backref
+/// compressed form and the share of bytes it saves. The compressed form is
+/// re-parsed and must tree-hash to the original before it is printed; when
+/// it is no smaller than the input, the input is kept.
+fn minimize(input: &str) -> Result<()> {
+    let mut allocator = Allocator::new();
+    let node = load_program(&mut allocator, input)?;
+    let expanded = node_to_bytes(&allocator, node)?;
+    let original = if looks_like_hex(input) {
+        decode_hex_input(input)?
+    } else {
+        expanded.clone()
+    };
+    let compressed = node_to_bytes_backrefs(&allocator, node)?;
+    let minimized = if compressed.len() < original.len() {
+        compressed
+    } else {
+        original.clone()
+    };
+
+    let original_hash = tree_hash(&allocator, node);
+    let mut check = Allocator::new();
+    let reparsed = node_from_bytes_backrefs(&mut check, &minimized)?;
+    if tree_hash(&check, reparsed) != original_hash {
+        bail!("minimized form does not round-trip to the original tree hash");
+    }
+
+    let saved = original.len() - minimized.len();
+    println!("original_len_bytes: {}", original.len());
+    println!("expanded_len_bytes: {}", expanded.len());
+    println!("minimized_len_bytes: {}", minimized.len());
+    println!(
+        "saved_percent: {:.1}",
+        if original.is_empty() { 0.0 } else { saved as f64 * 100.0 / original.len() as f64 }
+    );
+    println!("tree_hash: 0x{}", hex::encode(original_hash.to_bytes()));
+    println!("minimized: 0x{}", hex::encode(minimized));
+    Ok(())
+}
+
+/// Walks both trees in lockstep and returns the path (`f` = first, `r` =
+/// rest) to the first differing subtree along with both subtrees.
+fn first_difference(
+    allocator: &Allocator,
+    left: NodePtr,
+    right: NodePtr,
+    path: String,
+) -> (String, NodePtr, NodePtr) {
+    if let (SExp::Pair(lf, lr), SExp::Pair(rf, rr)) = (allocator.sexp(left), allocator.sexp(right)) {
+        if tree_hash(allocator, lf) != tree_hash(allocator, rf) {
+            return first_difference(allocator, lf, rf, path + "f");
+        }
+        if tree_hash(allocator, lr) != tree_hash(allocator, rr) {
+            return first_difference(allocator, lr, rr, path + "r");
+        }
+    }
+    (path, left, right)
+}
+
+fn run_for_equiv(allocator: &mut Allocator, program: NodePtr, env: NodePtr) -> Result<NodePtr, String> {
+    run_program(allocator, &ChiaDialect::new(0), program, env, DEFAULT_MAX_COST)
+        .map(|reduction| reduction.1)
+        .map_err(|err| format!("{err:?}"))
+}
+
+/// Hex is decoded (expanding backrefs); anything else is assembled as CLVM.
+fn load_program(allocator: &mut Allocator, input: &str) -> Result<NodePtr> {
+    if looks_like_hex(input) {
+        return Ok(node_from_bytes_backrefs(allocator, &decode_hex_input(input)?)?);
+    }
+    assemble(allocator, input).map_err(|e| anyhow::anyhow!("failed to assemble CLVM: {e}"))
+}
+
+/// Hex is disassembled with the latest operator names since `brun`
+/// re-assembles the text with its own (latest) keyword table.
 fn normalize_program_input(input: &str) -> Result<String> {
     if looks_like_hex(input) {
         let bytes = decode_hex_input(input)?;